@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE TABLE `scheduled_jobs` (
+  `job_name` VARCHAR(64) NOT NULL,
+  `last_run` BIGINT NOT NULL,
+  PRIMARY KEY (`job_name`)
+)
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE `guild_settings`
+  ADD COLUMN `timezone` VARCHAR(64) NOT NULL DEFAULT 'UTC',
+  ADD COLUMN `vctrack_reset_month` CHAR(7) DEFAULT NULL
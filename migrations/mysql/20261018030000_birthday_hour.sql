@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE `guild_settings` ADD COLUMN `birthday_hour` TINYINT UNSIGNED NOT NULL DEFAULT 10
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE `guild_settings`
+  ADD COLUMN `birthday_belated_days` TINYINT UNSIGNED NOT NULL DEFAULT 0,
+  ADD COLUMN `birthday_last_announced` DATE DEFAULT NULL
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE `guild_settings` ADD COLUMN `leap_day_policy` VARCHAR(8) NOT NULL DEFAULT 'feb28'
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE `birthday` ADD COLUMN `private` BOOL NOT NULL DEFAULT false
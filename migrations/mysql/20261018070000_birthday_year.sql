@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE `birthday`
+  ADD COLUMN `birthyear` SMALLINT UNSIGNED DEFAULT NULL,
+  ADD COLUMN `show_age` BOOL NOT NULL DEFAULT false
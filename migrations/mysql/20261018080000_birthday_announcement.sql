@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE `guild_settings`
+  ADD COLUMN `birthday_mention` VARCHAR(8) NOT NULL DEFAULT 'everyone',
+  ADD COLUMN `birthday_mention_role` BIGINT UNSIGNED DEFAULT NULL,
+  ADD COLUMN `birthday_message` TEXT DEFAULT NULL
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE `guild_settings` ADD COLUMN `birthday_combine` BOOLEAN NOT NULL DEFAULT FALSE
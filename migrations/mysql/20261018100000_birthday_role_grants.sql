@@ -0,0 +1,9 @@
+-- Add migration script here
+CREATE TABLE `birthday_role_grants` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `user_id` BIGINT UNSIGNED NOT NULL,
+  `role_id` BIGINT UNSIGNED NOT NULL,
+  `expires_at` BIGINT NOT NULL,
+  PRIMARY KEY (`guild_id`, `user_id`, `role_id`),
+  KEY `expires_at` (`expires_at`)
+)
@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE `user_settings` ADD COLUMN `birthday_reminder_days` TINYINT UNSIGNED DEFAULT NULL;
+
+CREATE TABLE `birthday_reminders` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `user_id` BIGINT UNSIGNED NOT NULL,
+  `target_user_id` BIGINT UNSIGNED NOT NULL,
+  PRIMARY KEY (`guild_id`, `user_id`, `target_user_id`)
+)
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE `quotes`
+  ADD COLUMN `message_link` VARCHAR(128) DEFAULT NULL,
+  ADD COLUMN `channel_id` BIGINT UNSIGNED DEFAULT NULL
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE `quotes`
+  ADD COLUMN `deleted_at` BIGINT DEFAULT NULL,
+  ADD COLUMN `deleted_by` BIGINT UNSIGNED DEFAULT NULL
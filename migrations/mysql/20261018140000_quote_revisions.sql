@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE `quote_revisions` (
+  `revision_id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `quote_id` INT UNSIGNED NOT NULL,
+  `editor_id` BIGINT UNSIGNED NOT NULL,
+  `edited_at` BIGINT NOT NULL,
+  `old_quote` VARCHAR(500) NOT NULL,
+  `old_sayer_id` BIGINT UNSIGNED NOT NULL,
+  `old_sayer_display_name` VARCHAR(32) NOT NULL,
+  `old_timestamp` DATE NOT NULL,
+  PRIMARY KEY (`revision_id`),
+  KEY `guild_quote` (`guild_id`, `quote_id`)
+)
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE `guild_settings`
+  ADD COLUMN `qotd_channel` BIGINT UNSIGNED DEFAULT NULL,
+  ADD COLUMN `qotd_hour` TINYINT UNSIGNED NOT NULL DEFAULT 12,
+  ADD COLUMN `qotd_last_posted` DATE DEFAULT NULL;
+
+ALTER TABLE `quotes` ADD COLUMN `featured_at` BIGINT DEFAULT NULL
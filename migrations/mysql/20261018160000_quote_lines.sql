@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE `quote_lines` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `quote_id` INT UNSIGNED NOT NULL,
+  `line_number` TINYINT UNSIGNED NOT NULL,
+  `speaker_id` BIGINT UNSIGNED NOT NULL,
+  `speaker_display_name` VARCHAR(32) NOT NULL,
+  `line` VARCHAR(200) NOT NULL,
+  PRIMARY KEY (`guild_id`, `quote_id`, `line_number`),
+  KEY `guild_speaker` (`guild_id`, `speaker_id`)
+)
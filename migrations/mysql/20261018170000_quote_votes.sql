@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE `quote_votes` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `quote_id` INT UNSIGNED NOT NULL,
+  `user_id` BIGINT UNSIGNED NOT NULL,
+  PRIMARY KEY (`guild_id`, `quote_id`, `user_id`)
+)
@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE `quote_tags` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `quote_id` INT UNSIGNED NOT NULL,
+  `tag` VARCHAR(32) NOT NULL,
+  PRIMARY KEY (`guild_id`, `quote_id`, `tag`),
+  KEY `guild_tag` (`guild_id`, `tag`)
+)
@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE `birthday_announcements` (
+  `guild_id` BIGINT UNSIGNED NOT NULL,
+  `user_id` BIGINT UNSIGNED NOT NULL,
+  `birthday_date` DATE NOT NULL,
+  PRIMARY KEY (`guild_id`, `birthday_date`, `user_id`)
+)
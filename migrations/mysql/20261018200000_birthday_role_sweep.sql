@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE `guild_settings` ADD COLUMN `birthday_role_sweep_pending` BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Birthday roles given out before grants were tracked are never taken away, so those guilds get swept once
+UPDATE `guild_settings` SET `birthday_role_sweep_pending` = TRUE WHERE `birthday_role` IS NOT NULL
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE `guild_settings` ADD COLUMN `birthday_last_reminded` DATE DEFAULT NULL
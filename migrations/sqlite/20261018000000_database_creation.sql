@@ -0,0 +1,106 @@
+-- Add migration script here
+-- Consolidated schema matching the MySQL migrations up to 20250426011038_bot_gif_settings
+CREATE TABLE birthday (
+  guild_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  birthday INTEGER NOT NULL,
+  birthmonth INTEGER NOT NULL,
+  nickname TEXT DEFAULT NULL,
+  PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE guild_settings (
+  guild_id INTEGER NOT NULL,
+  birthday_channel INTEGER DEFAULT NULL,
+  birthday_role INTEGER DEFAULT NULL,
+  vctrack_ignored_channel INTEGER DEFAULT NULL,
+  quotes_required_role INTEGER DEFAULT NULL,
+  member_leave_channel_id INTEGER DEFAULT NULL,
+  custom_gifs_required_role INTEGER DEFAULT NULL,
+  roulette_chamber INTEGER NOT NULL DEFAULT 0,
+  roulette_count INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (guild_id)
+);
+
+CREATE TABLE users (
+  guild_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  cookie_sent INTEGER NOT NULL DEFAULT 0,
+  cookie_received INTEGER NOT NULL DEFAULT 0,
+  slap_sent INTEGER NOT NULL DEFAULT 0,
+  slap_received INTEGER NOT NULL DEFAULT 0,
+  cake_sent INTEGER NOT NULL DEFAULT 0,
+  cake_received INTEGER NOT NULL DEFAULT 0,
+  cake_glados INTEGER NOT NULL DEFAULT 0,
+  tea_sent INTEGER NOT NULL DEFAULT 0,
+  tea_received INTEGER NOT NULL DEFAULT 0,
+  bomb_sent INTEGER NOT NULL DEFAULT 0,
+  bomb_defused INTEGER NOT NULL DEFAULT 0,
+  bomb_failed INTEGER NOT NULL DEFAULT 0,
+  vctrack_join_time INTEGER NOT NULL DEFAULT 0,
+  vctrack_total_time INTEGER NOT NULL DEFAULT 0,
+  vctrack_monthly_time INTEGER NOT NULL DEFAULT 0,
+  display_name TEXT NOT NULL DEFAULT '',
+  rps_win INTEGER NOT NULL DEFAULT 0,
+  rps_loss INTEGER NOT NULL DEFAULT 0,
+  rps_tie INTEGER NOT NULL DEFAULT 0,
+  roulette_deaths INTEGER NOT NULL DEFAULT 0,
+  hug_sent INTEGER NOT NULL DEFAULT 0,
+  hug_received INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE user_settings (
+  guild_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  command_ping BOOLEAN NOT NULL DEFAULT TRUE,
+  PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE welcome (
+  guild_id INTEGER NOT NULL,
+  channel_id INTEGER DEFAULT NULL,
+  image_url TEXT DEFAULT NULL,
+  message TEXT DEFAULT NULL,
+  PRIMARY KEY (guild_id)
+);
+
+CREATE TABLE boost (
+  guild_id INTEGER NOT NULL,
+  channel_id INTEGER DEFAULT NULL,
+  image_url TEXT DEFAULT NULL,
+  message TEXT DEFAULT NULL,
+  PRIMARY KEY (guild_id)
+);
+
+CREATE TABLE quotes (
+  guild_id INTEGER NOT NULL,
+  adder_id INTEGER NOT NULL,
+  sayer_id INTEGER NOT NULL,
+  quote_id INTEGER NOT NULL,
+  quote TEXT NOT NULL,
+  timestamp DATE NOT NULL,
+  sayer_display_name TEXT NOT NULL DEFAULT '',
+  adder_display_name TEXT NOT NULL DEFAULT '',
+  PRIMARY KEY (guild_id, quote_id)
+);
+
+CREATE TABLE custom_gifs (
+  guild_id INTEGER NOT NULL,
+  gif_type TEXT NOT NULL,
+  gif_id INTEGER NOT NULL,
+  gif_url TEXT NOT NULL DEFAULT '',
+  gif_name TEXT NOT NULL,
+  PRIMARY KEY (guild_id, gif_type, gif_id)
+);
+
+CREATE TABLE bot_settings (
+  glados_gif TEXT NOT NULL,
+  roulette_click_gif TEXT NOT NULL,
+  roulette_fire_gif TEXT NOT NULL
+);
+INSERT INTO bot_settings (glados_gif, roulette_click_gif, roulette_fire_gif) VALUES (
+  'http://p6mapclo.s2.myws.ca/panik_gifs/portal-glados.gif',
+  'http://p6mapclo.s2.myws.ca/panik_gifs/roulette_click.gif',
+  'http://p6mapclo.s2.myws.ca/panik_gifs/roulette_fire.gif'
+);
@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE TABLE scheduled_jobs (
+  job_name TEXT NOT NULL,
+  last_run INTEGER NOT NULL,
+  PRIMARY KEY (job_name)
+);
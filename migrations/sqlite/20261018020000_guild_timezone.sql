@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE guild_settings ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
+ALTER TABLE guild_settings ADD COLUMN vctrack_reset_month TEXT DEFAULT NULL;
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE guild_settings ADD COLUMN birthday_hour INTEGER NOT NULL DEFAULT 10;
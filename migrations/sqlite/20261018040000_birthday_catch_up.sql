@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE guild_settings ADD COLUMN birthday_belated_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE guild_settings ADD COLUMN birthday_last_announced DATE DEFAULT NULL;
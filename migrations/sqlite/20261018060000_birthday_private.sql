@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE birthday ADD COLUMN private BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE birthday ADD COLUMN birthyear INTEGER DEFAULT NULL;
+ALTER TABLE birthday ADD COLUMN show_age BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE guild_settings ADD COLUMN birthday_mention TEXT NOT NULL DEFAULT 'everyone';
+ALTER TABLE guild_settings ADD COLUMN birthday_mention_role INTEGER DEFAULT NULL;
+ALTER TABLE guild_settings ADD COLUMN birthday_message TEXT DEFAULT NULL;
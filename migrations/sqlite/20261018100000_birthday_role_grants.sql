@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE birthday_role_grants (
+  guild_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  role_id INTEGER NOT NULL,
+  expires_at INTEGER NOT NULL,
+  PRIMARY KEY (guild_id, user_id, role_id)
+);
+
+CREATE INDEX birthday_role_grants_expires_at ON birthday_role_grants (expires_at);
@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE user_settings ADD COLUMN birthday_reminder_days INTEGER DEFAULT NULL;
+
+CREATE TABLE birthday_reminders (
+  guild_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  target_user_id INTEGER NOT NULL,
+  PRIMARY KEY (guild_id, user_id, target_user_id)
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE quotes ADD COLUMN message_link TEXT DEFAULT NULL;
+ALTER TABLE quotes ADD COLUMN channel_id INTEGER DEFAULT NULL;
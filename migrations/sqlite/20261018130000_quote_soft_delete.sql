@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE quotes ADD COLUMN deleted_at INTEGER DEFAULT NULL;
+ALTER TABLE quotes ADD COLUMN deleted_by INTEGER DEFAULT NULL;
@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE quote_revisions (
+  revision_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  guild_id INTEGER NOT NULL,
+  quote_id INTEGER NOT NULL,
+  editor_id INTEGER NOT NULL,
+  edited_at INTEGER NOT NULL,
+  old_quote TEXT NOT NULL,
+  old_sayer_id INTEGER NOT NULL,
+  old_sayer_display_name TEXT NOT NULL,
+  old_timestamp DATE NOT NULL
+);
+
+CREATE INDEX quote_revisions_guild_quote ON quote_revisions (guild_id, quote_id);
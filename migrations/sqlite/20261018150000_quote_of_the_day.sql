@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TABLE guild_settings ADD COLUMN qotd_channel INTEGER DEFAULT NULL;
+ALTER TABLE guild_settings ADD COLUMN qotd_hour INTEGER NOT NULL DEFAULT 12;
+ALTER TABLE guild_settings ADD COLUMN qotd_last_posted DATE DEFAULT NULL;
+
+ALTER TABLE quotes ADD COLUMN featured_at INTEGER DEFAULT NULL;
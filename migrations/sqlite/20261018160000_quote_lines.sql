@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE quote_lines (
+  guild_id INTEGER NOT NULL,
+  quote_id INTEGER NOT NULL,
+  line_number INTEGER NOT NULL,
+  speaker_id INTEGER NOT NULL,
+  speaker_display_name TEXT NOT NULL,
+  line TEXT NOT NULL,
+  PRIMARY KEY (guild_id, quote_id, line_number)
+);
+
+CREATE INDEX quote_lines_guild_speaker ON quote_lines (guild_id, speaker_id);
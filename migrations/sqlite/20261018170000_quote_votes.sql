@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE quote_votes (
+  guild_id INTEGER NOT NULL,
+  quote_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  PRIMARY KEY (guild_id, quote_id, user_id)
+);
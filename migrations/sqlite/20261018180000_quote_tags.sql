@@ -0,0 +1,9 @@
+-- Add migration script here
+CREATE TABLE quote_tags (
+  guild_id INTEGER NOT NULL,
+  quote_id INTEGER NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (guild_id, quote_id, tag)
+);
+
+CREATE INDEX quote_tags_guild_tag ON quote_tags (guild_id, tag);
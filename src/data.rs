@@ -1,15 +1,16 @@
-use crate::Context;
+use crate::{Context, Error};
+use crate::storage::Storage;
 use poise::serenity_prelude as serenity;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, USER_AGENT};
 use std::time::Duration;
-use tracing::{info, error};
+use tracing::{info, warn, error};
 
 //--------------------------
 // Handler for the bot data
 //--------------------------
 #[derive(Debug, Clone)]
 pub struct Data { // User data, which is stored and accessible in all command invocations
-    pub database: sqlx::MySqlPool,
+    pub database: Storage,
     pub client: reqwest::Client,
 }
 
@@ -17,7 +18,24 @@ impl Data {
     pub async fn init() -> Self {
         // Set Data
         let database_url = std::env::var("DATABASE_URL").expect("missing DATABASE_URL");
-        let database = sqlx::mysql::MySqlPool::connect(&database_url).await.unwrap();
+        let database = Storage::connect(&database_url).await.unwrap();
+        info!("[ BOT ] Connected to the {} database", database.backend_name());
+
+        // Bring the schema up to date unless migrations are handled outside the bot
+        let skip_migrations = std::env::var("SKIP_MIGRATIONS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let migration_result = if skip_migrations {
+            warn!("[ DATABASE ] SKIP_MIGRATIONS is set, only verifying applied migrations");
+            database.verify_migrations().await
+        } else {
+            database.run_migrations().await
+        };
+
+        // Refuse to start on a schema that doesn't match the one the bot was built against
+        if let Err(e) = migration_result {
+            error!("[ DATABASE ] Unable to migrate the database, refusing to start: {e}");
+            std::process::exit(1);
+        }
+        info!("[ DATABASE ] Database schema is up to date");
 
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, HeaderValue::from_static("AmethystBot/1.0"));
@@ -39,37 +57,29 @@ impl Data {
 //--------------------------
 // User table checker
 //--------------------------
-pub async fn user_table_check(ctx: Context<'_>, user: &serenity::User) {
+pub async fn user_table_check(ctx: Context<'_>, user: &serenity::User) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
 
-    // Grab user info
-    let db_user = sqlx::query!("SELECT display_name, COUNT(user_id) AS count FROM users WHERE guild_id = ? AND user_id = ?", guild_id.get(), user.id.get())
-            .fetch_one(&ctx.data().database)
-            .await
-            .unwrap();
-
     // If user doesn't exist, add them. Returns after adding
-    if db_user.count == 0 {
+    if !ctx.data().database.user_exists(guild_id.get(), user.id.get()).await? {
         user_table_add(&ctx.data().database, guild_id.get(), user.id.get(), user.display_name().to_string()).await;
     }
+
+    Ok(())
 }
 
 //--------------------------
 // Add user to database
 //--------------------------
-pub async fn user_table_add(database: &sqlx::MySqlPool, guild_id: u64, user_id: u64, display_name: String) {
-    let users_table_query_attempt = sqlx::query!("INSERT INTO users (guild_id, user_id, display_name) VALUES (?, ?, ?)", guild_id, user_id, display_name)
-        .execute(database)
-        .await;
+pub async fn user_table_add(database: &Storage, guild_id: u64, user_id: u64, display_name: String) {
+    let users_table_query_attempt = database.insert_user(guild_id, user_id, &display_name).await;
 
     match users_table_query_attempt {
         Ok(_) => info!("[ USER ] New user added to stats table - Guild ID: {guild_id} - User ID: {user_id}"),
         Err(e) => error!("[ USER ] An error occurred adding user to stats table: {e}")
     }
     
-    let user_settings_query = sqlx::query!("INSERT INTO user_settings (guild_id, user_id) VALUES (?, ?)", guild_id, user_id)
-        .execute(database)
-        .await;
+    let user_settings_query = database.insert_user_settings(guild_id, user_id).await;
         
     match user_settings_query {
         Ok(_) => info!("[ USER ] New user added to user_settings table - Guild ID: {guild_id} - User ID: {user_id}"),
@@ -81,25 +91,24 @@ pub async fn user_table_add(database: &sqlx::MySqlPool, guild_id: u64, user_id:
 // Alter display name in DB if needed
 //--------------------------
 pub async fn alter_db_display_name(
-    database: &sqlx::MySqlPool,
+    database: &Storage,
     guild_id: u64,
     user_id: u64,
     display_name: String
-) {
-    let query = sqlx::query!("UPDATE users SET display_name = ? WHERE guild_id = ? AND user_id = ? AND display_name != ?", display_name, guild_id, user_id, display_name)
-            .execute(database)
-            .await
-            .unwrap();
+) -> Result<(), Error> {
+    let changed = database.update_display_name(guild_id, user_id, &display_name).await?;
     
-    if query.rows_affected() == 1 {
+    if changed {
         info!("[ USER ] Changed user display name in user stats table - Guild ID: {guild_id} - User ID: {user_id}");
     }
+
+    Ok(())
 }
 
 //--------------------------
 // User structure
 //--------------------------
-#[derive(Debug)]
+#[derive(Debug, sqlx::FromRow)]
 #[allow(dead_code)]
 pub struct User {
     pub guild_id: u64,
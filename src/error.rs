@@ -0,0 +1,129 @@
+use crate::Data;
+use poise::serenity_prelude as serenity;
+use tracing::{error, warn};
+
+//--------------------------
+// Bot error type
+//--------------------------
+#[derive(Debug)]
+pub enum BotError {
+    Database(sqlx::Error),
+    Discord(Box<serenity::Error>),
+    Scryfall(String),
+    Validation(String),
+    Permission(String),
+}
+
+impl BotError {
+    // The message shown to the user running the command
+    pub fn user_message(&self) -> String {
+        match self {
+            BotError::Database(_) => String::from("Something went wrong while talking to the database! Please try again in a moment."),
+            BotError::Discord(_) => String::from("Discord had trouble processing that request! Please try again in a moment."),
+            BotError::Scryfall(msg) => msg.to_string(),
+            BotError::Validation(msg) => msg.to_string(),
+            BotError::Permission(msg) => msg.to_string(),
+        }
+    }
+
+    // Whether the error is caused by the bot rather than the user's input
+    fn is_internal(&self) -> bool {
+        matches!(self, BotError::Database(_) | BotError::Discord(_))
+    }
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::Database(e) => write!(f, "Database error: {e}"),
+            BotError::Discord(e) => write!(f, "Discord error: {e}"),
+            BotError::Scryfall(msg) => write!(f, "Scryfall error: {msg}"),
+            BotError::Validation(msg) => write!(f, "Validation error: {msg}"),
+            BotError::Permission(msg) => write!(f, "Permission error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::Database(e) => Some(e),
+            BotError::Discord(e) => Some(e.as_ref()),
+            _ => None
+        }
+    }
+}
+
+impl From<sqlx::Error> for BotError {
+    fn from(e: sqlx::Error) -> Self {
+        BotError::Database(e)
+    }
+}
+
+impl From<serenity::Error> for BotError {
+    fn from(e: serenity::Error) -> Self {
+        BotError::Discord(Box::new(e))
+    }
+}
+
+// Plain strings are messages meant for the user, e.g. `Err("No quote saved with that ID!".into())`
+impl From<String> for BotError {
+    fn from(msg: String) -> Self {
+        BotError::Validation(msg)
+    }
+}
+
+impl From<&str> for BotError {
+    fn from(msg: &str) -> Self {
+        BotError::Validation(msg.to_string())
+    }
+}
+
+//--------------------------
+// Framework error handler
+//--------------------------
+pub async fn on_error(error: poise::FrameworkError<'_, Data, BotError>) {
+    match error {
+        poise::FrameworkError::Command { error, ctx, .. } => report_command_error(ctx, &error).await,
+        poise::FrameworkError::CommandCheckFailed { error: Some(error), ctx, .. } => report_command_error(ctx, &error).await,
+        poise::FrameworkError::EventHandler { error, event, .. } => {
+            error!("[ EVENT ] An error occurred handling the {} event: {error:?}", event.snake_case_name());
+        },
+        error => {
+            if let Err(e) = poise::builtins::on_error(error).await {
+                error!("[ BOT ] An error occurred while handling a framework error: {e}");
+            }
+        }
+    }
+}
+
+// Log the full cause of a command error and show the user a short explanation
+async fn report_command_error(ctx: crate::Context<'_>, error: &BotError) {
+    let guild = match ctx.guild_id() {
+        Some(guild_id) => format!("Guild ID: {}", guild_id.get()),
+        None => String::from("User DM")
+    };
+
+    if error.is_internal() {
+        error!("[ COMMAND ] Command failed - {guild} - User ID: {} - Command: {} - {error:?}",
+            ctx.author().id.get(),
+            ctx.command().qualified_name);
+    } else {
+        warn!("[ COMMAND ] Command rejected - {guild} - User ID: {} - Command: {} - {error}",
+            ctx.author().id.get(),
+            ctx.command().qualified_name);
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .colour(0xFF0000)
+        .description(error.user_message());
+
+    let reply = ctx.send(poise::CreateReply::default()
+        .embed(embed)
+        .ephemeral(true)
+    ).await;
+
+    if let Err(e) = reply {
+        error!("[ COMMAND ] Unable to send error message to user: {e}");
+    }
+}
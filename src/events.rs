@@ -3,6 +3,7 @@
 use poise::serenity_prelude as serenity;
 use serenity::VoiceState;
 use crate::{Data, Error};
+use chrono::Utc;
 use tracing::warn;
 
 // "Custom" Event Calls
@@ -16,18 +17,11 @@ pub async fn on_user_vc_connect(data: &Data, old: &Option<VoiceState>, new: &Voi
     let user_id = new.user_id.get();
 
     // Set ignored channel id
-    let ignored_channel_id = sqlx::query!("SELECT vctrack_ignored_channel FROM guild_settings WHERE guild_id = ?", guild_id)
-            .fetch_one(&data.database)
-            .await
-            .unwrap();
-    let ignored_channel_id = ignored_channel_id.vctrack_ignored_channel.unwrap_or(0);
+    let ignored_channel_id = data.database.get_vctrack_ignored_channel(guild_id).await?.unwrap_or(0);
 
     // Check if new.channel_id isn't ignored channel
     if new.channel_id.unwrap().get() != ignored_channel_id {
-        sqlx::query!("UPDATE users SET vctrack_join_time = UNIX_TIMESTAMP() WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-            .execute(&data.database)
-            .await
-            .unwrap();
+        data.database.set_vc_join_time(guild_id, user_id, Utc::now().timestamp()).await?;
     }
 
     Ok(())
@@ -41,34 +35,19 @@ pub async fn on_user_vc_disconnect(data: &Data, old: &Option<VoiceState>, new: &
     let user_id = new.user_id.get();
 
     // Check for ignored channel
-    let ignored_channel_id = sqlx::query!("SELECT vctrack_ignored_channel FROM guild_settings WHERE guild_id = ?", guild_id)
-            .fetch_one(&data.database)
-            .await
-            .unwrap();
-    let ignored_channel_id = ignored_channel_id.vctrack_ignored_channel.unwrap_or(0);
+    let ignored_channel_id = data.database.get_vctrack_ignored_channel(guild_id).await?.unwrap_or(0);
 
     // Check if user join time is 0
-    let join_time = sqlx::query!("SELECT vctrack_join_time FROM users WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_one(&data.database)
-        .await
-        .unwrap();
+    let join_time = data.database.get_vc_join_time(guild_id, user_id).await?;
 
-    if join_time.vctrack_join_time == 0 && old.as_ref().unwrap().channel_id.unwrap().get() != ignored_channel_id {
+    if join_time == 0 && old.as_ref().unwrap().channel_id.unwrap().get() != ignored_channel_id {
         warn!("[ VCTRACKER ] SAFEGUARD - Skipping user's time update. Guild ID: {guild_id} - User ID: {user_id}");
         return Ok(());
     }
 
     // Check if old.channel_id isn't ignored channel
     if old.as_ref().unwrap().channel_id.unwrap().get() != ignored_channel_id {
-        let query = format!("
-            UPDATE users SET vctrack_total_time = vctrack_total_time + (UNIX_TIMESTAMP() - vctrack_join_time) WHERE guild_id = {guild_id} AND user_id = {user_id};
-            UPDATE users SET vctrack_monthly_time = vctrack_monthly_time + (UNIX_TIMESTAMP() - vctrack_join_time) WHERE guild_id = {guild_id} AND user_id = {user_id};
-            UPDATE users SET vctrack_join_time = 0 WHERE guild_id = {guild_id} AND user_id = {user_id}");
-
-        sqlx::raw_sql(&query)
-            .execute(&data.database)
-            .await
-            .unwrap();
+        data.database.add_vc_time(guild_id, user_id, Utc::now().timestamp(), 0).await?;
     }
 
     Ok(())
@@ -82,11 +61,7 @@ pub async fn on_user_vc_move(data: &Data, old: &Option<VoiceState>, new: &VoiceS
     let user_id = new.user_id.get();
 
     // Set ignored channel id
-    let ignored_channel_id = sqlx::query!("SELECT vctrack_ignored_channel FROM guild_settings WHERE guild_id = ?", guild_id)
-            .fetch_one(&data.database)
-            .await
-            .unwrap();
-    let ignored_channel_id = ignored_channel_id.vctrack_ignored_channel.unwrap_or(0);
+    let ignored_channel_id = data.database.get_vctrack_ignored_channel(guild_id).await?.unwrap_or(0);
     
     let old_cid = old.as_ref().unwrap().channel_id.unwrap().get();
     let new_cid = new.channel_id.unwrap().get();
@@ -96,34 +71,20 @@ pub async fn on_user_vc_move(data: &Data, old: &Option<VoiceState>, new: &VoiceS
     // - If not, act as if connecting
     if old_cid != new_cid && new_cid == ignored_channel_id {
         // Check if user join time is 0
-        let join_time = sqlx::query!("SELECT vctrack_join_time FROM users WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-            .fetch_one(&data.database)
-            .await
-            .unwrap();
+        let join_time = data.database.get_vc_join_time(guild_id, user_id).await?;
 
-        if join_time.vctrack_join_time == 0 {
+        if join_time == 0 {
         warn!("[ VCTRACKER ] SAFEGUARD - Skipping user's time update. Guild ID: {guild_id} - User ID: {user_id}");
             return Ok(());
         }
 
-        let query = format!("
-            UPDATE users SET vctrack_total_time = vctrack_total_time + (UNIX_TIMESTAMP() - vctrack_join_time) WHERE guild_id = {guild_id} AND user_id = {user_id};
-            UPDATE users SET vctrack_monthly_time = vctrack_monthly_time + (UNIX_TIMESTAMP() - vctrack_join_time) WHERE guild_id = {guild_id} AND user_id = {user_id};
-            UPDATE users SET vctrack_join_time = 0 WHERE guild_id = {guild_id} AND user_id = {user_id}");
-
-        sqlx::raw_sql(&query)
-            .execute(&data.database)
-            .await
-            .unwrap();
+        data.database.add_vc_time(guild_id, user_id, Utc::now().timestamp(), 0).await?;
 
         return Ok(());
     }
 
     if old_cid != new_cid && old_cid == ignored_channel_id {
-        sqlx::query!("UPDATE users SET vctrack_join_time = UNIX_TIMESTAMP() WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-            .execute(&data.database)
-            .await
-            .unwrap();
+        data.database.set_vc_join_time(guild_id, user_id, Utc::now().timestamp()).await?;
     }
 
     Ok(())
@@ -1,71 +1,35 @@
 mod data;
 mod modules;
 mod events;
+mod error;
+mod storage;
+mod scheduler;
+mod pagination;
 
 use data::{alter_db_display_name, user_table_add, user_table_check, Data};
 use poise::serenity_prelude as serenity;
 use dotenv::dotenv;
-use chrono::Utc;
-use cron::Schedule;
-use std::{str::FromStr, sync::Arc};
-use std::sync::Once;
 use modules::*;
-use tracing::{warn, info};
+use tracing::{warn, info, error};
 
-type Error = Box<dyn std::error::Error + Send + Sync>;
+type Error = error::BotError;
 type Context<'a> = poise::Context<'a, Data, Error>;
-static BIRTHDAY: Once = Once::new();
-static VCMONTHLY: Once = Once::new();
 
 async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framework: poise::FrameworkContext<'_, Data, Error>, data: &Data) -> Result<(), Error> {
     match event {
         serenity::FullEvent::Ready { .. } => {
             info!("[ BOT ] AmethystBot is online!");
-            let ctx = Arc::new(ctx.clone());
-            let data = Arc::new(data.clone());
-
-            BIRTHDAY.call_once(|| {
-                // Birthday loop
-                let new_ctx = Arc::clone(&ctx);
-                let new_data = Arc::clone(&data);
-                tokio::spawn(async move {
-                    loop {
-                        birthday::birthday_check(&new_ctx, &new_data).await;
-
-                        // Calculate sleep until the next proper birthday time
-                        let current_time = chrono::Utc::now();
-                        let expression = "0 0 10 * * * *";
-                        let schedule = Schedule::from_str(expression).unwrap();
-                        let schedule: Vec<_> = schedule.upcoming(Utc).take(1).collect();
-                        let duration = schedule[0].signed_duration_since(current_time);
-                        
-                        info!("[ BIRTHDAY ] Seconds until next birthday check: {}", duration.num_seconds());
+            scheduler::start(ctx, data);
 
-                        tokio::time::sleep(duration.to_std().unwrap()).await;
-                    }
-                });
-            });
-
-            VCMONTHLY.call_once(|| {
-                // VCTracker Monthly Reset
-                let new_data = Arc::clone(&data);
-                tokio::spawn(async move {
-                    loop {
-                        vctracker::vctracker_reset_monthly(&new_data.database).await;
-
-                        // Calculate sleep to 1st of next month
-                        let current_time = chrono::Utc::now();
-                        let expression = "0 0 0 1 * * *";
-                        let schedule = Schedule::from_str(expression).unwrap();
-                        let schedule: Vec<_> = schedule.upcoming(Utc).take(1).collect();
-                        let duration = schedule[0].signed_duration_since(current_time);
-
-                        info!("[ VCTRACKER ] Seconds until next monthly reset: {}", duration.num_seconds());
-
-                        tokio::time::sleep(duration.to_std().unwrap()).await;
-                    }
-                });
-            });
+            // Birthday roles that should have ended while the bot was offline
+            if let Err(e) = birthday::expire_birthday_roles(ctx, data).await {
+                error!("[ BIRTHDAY ] Unable to clean up expired birthday roles: {e:?}");
+            }
+
+            // Birthday roles given out before they were tracked, only needed once after upgrading
+            if let Err(e) = birthday::sweep_legacy_birthday_roles(ctx, data).await {
+                error!("[ BIRTHDAY ] Unable to sweep old birthday roles: {e:?}");
+            }
         },
 
         serenity::FullEvent::GuildCreate { guild, is_new } => {
@@ -74,15 +38,7 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
 
             // Add guild to settings tables if new to the bot
             if is_new {
-                let query = format!("
-                    INSERT IGNORE INTO guild_settings (guild_id) VALUES ({guild_id});
-                    INSERT IGNORE INTO welcome (guild_id) VALUES ({guild_id})
-                ");
-
-                sqlx::raw_sql(&query)
-                    .execute(&data.database)
-                    .await
-                    .unwrap();
+                data.database.register_guild(guild_id).await?;
 
                 info!("[ BOT ] Registering new guild into \"welcome\" table - ID: {}", guild_id);
                 info!("[ BOT ] Registering new guild into \"guild_settings\" table - ID: {}", guild_id);
@@ -92,21 +48,15 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
 
             // VC tracking safeguard for disconnect users
             let guild_voice_states = guild.voice_states.clone();
-            let user_join_times = sqlx::query!("SELECT user_id, vctrack_join_time FROM users WHERE guild_id = ? AND vctrack_join_time != 0", guild_id)
-                .fetch_all(&data.database)
-                .await
-                .unwrap();
+            let joined_user_ids = data.database.get_vc_joined_users(guild_id).await?;
 
-            for user in user_join_times {
-                match guild_voice_states.get(&serenity::UserId::new(user.user_id)) {
+            for user_id in joined_user_ids {
+                match guild_voice_states.get(&serenity::UserId::new(user_id)) {
                     Some(_) => continue,
                     None => {
-                        sqlx::query!("UPDATE users SET vctrack_join_time = 0 WHERE guild_id = ? AND user_id = ?", guild_id, user.user_id)
-                            .execute(&data.database)
-                            .await
-                            .unwrap();
+                        data.database.set_vc_join_time(guild_id, user_id, 0).await?;
 
-                        warn!("[ VCTRACKER ] SAFEGUARD - Adjusted time for User ID ({})- Guild ID: {}", user.user_id, guild_id);
+                        warn!("[ VCTRACKER ] SAFEGUARD - Adjusted time for User ID ({})- Guild ID: {}", user_id, guild_id);
                     }
                 }
             }
@@ -116,10 +66,7 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
             let guild_id = new_member.guild_id.get();
 
             // Grab all info and check for channel
-            let welcome = sqlx::query!("SELECT * FROM welcome WHERE guild_id = ?", guild_id)
-                .fetch_one(&data.database)
-                .await
-                .unwrap();
+            let welcome = data.database.get_welcome_settings(guild_id).await?;
 
             if welcome.channel_id.is_none() { return Ok(()); }
 
@@ -133,7 +80,7 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
 
             let channel = serenity::ChannelId::new(welcome.channel_id.unwrap());
 
-            channel.send_message(&ctx, serenity::CreateMessage::new().embed(welcome_embed)).await.unwrap();
+            channel.send_message(&ctx, serenity::CreateMessage::new().embed(welcome_embed)).await?;
             info!("[ WELCOME ] New welcome message posted - Guild ID: {guild_id}");
 
             user_table_add(&data.database, guild_id, new_member.user.id.get(), new_member.display_name().to_string()).await;
@@ -145,31 +92,18 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
             let user_id = user.id.get();
 
             // Remove user from birthday, users
-            let query = format!("
-                DELETE FROM birthday WHERE guild_id = {guild_id} AND user_id = {user_id};
-                DELETE FROM users WHERE guild_id = {guild_id} AND user_id = {user_id};
-                DELETE FROM user_settings WHERE guild_id = {guild_id} AND user_id = {user_id}
-            ");
-
-            sqlx::raw_sql(&query)
-                .execute(&data.database)
-                .await
-                .unwrap();
+            data.database.delete_member_data(guild_id, user_id).await?;
 
             info!("[ USER ] A user has left a server and associated data has been removed.");
 
             // Send leave message if channel is set in server settings
-            let leave_channel_id = sqlx::query!("SELECT member_leave_channel_id FROM guild_settings WHERE guild_id = ?", guild_id)
-                .fetch_one(&data.database)
-                .await
-                .unwrap()
-                .member_leave_channel_id;
-
-            if leave_channel_id.is_some() {
-                let channel_id = serenity::ChannelId::new(leave_channel_id.unwrap());
+            let leave_channel_id = data.database.get_member_leave_channel(guild_id).await?;
+
+            if let Some(leave_channel_id) = leave_channel_id {
+                let channel_id = serenity::ChannelId::new(leave_channel_id);
                 let msg = serenity::CreateMessage::new().content(format!("***{} has left the server.***", user.name));
 
-                channel_id.send_message(&ctx, msg).await.unwrap();
+                channel_id.send_message(&ctx, msg).await?;
             }
         },
 
@@ -179,7 +113,7 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
                 None => return Ok(())
             };
 
-            alter_db_display_name(&data.database, new.guild_id.get(), new.user.id.get(), new.display_name().to_string()).await;
+            alter_db_display_name(&data.database, new.guild_id.get(), new.user.id.get(), new.display_name().to_string()).await?;
         },
 
         serenity::FullEvent::Message { new_message } => {
@@ -190,10 +124,7 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
             // Handle boost message event
             if new_message.kind == serenity::MessageType::NitroBoost {
                 // Grab server's boost settings
-                let boost_settings = sqlx::query!("SELECT * FROM boost WHERE guild_id = ?", new_message.guild_id.unwrap().get())
-                    .fetch_one(&data.database)
-                    .await
-                    .unwrap();
+                let boost_settings = data.database.get_boost_settings(new_message.guild_id.unwrap().get()).await?;
                     
                 // Do the boost message embed thing
                 if let Some(boost_channel) = boost_settings.channel_id {
@@ -214,6 +145,14 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
             }
         },
 
+        serenity::FullEvent::InteractionCreate { interaction } => {
+            // Quote upvote buttons aren't tied to a running command, so they're handled here
+            if let serenity::Interaction::Component(press) = interaction
+                && press.data.custom_id.starts_with(quotes::UPVOTE_PREFIX) {
+                quotes::handle_upvote(ctx, data, press).await?;
+            }
+        },
+
         serenity::FullEvent::VoiceStateUpdate { old, new } => {
             // Check for user in the database
             // We do it here manually since the data::user_table_check function requires poise::Context and not serenity::Content.
@@ -221,13 +160,8 @@ async fn listener(ctx: &serenity::Context, event: &serenity::FullEvent, _framewo
             let guild_id = new.guild_id.unwrap();
             let user_id = new.user_id;
 
-            let db_user = sqlx::query!("SELECT display_name, COUNT(user_id) AS count FROM users WHERE guild_id = ? AND user_id = ?", guild_id.get(), user_id.get())
-                .fetch_one(&data.database)
-                .await
-                .unwrap();
-
             // If user doesn't exist, add them. Returns after adding
-            if db_user.count == 0 {
+            if !data.database.user_exists(guild_id.get(), user_id.get()).await? {
                 let display_name = new.member.as_ref().unwrap().display_name().to_string();
                 user_table_add(&data.database, guild_id.get(), user_id.get(), display_name).await;
             }
@@ -269,11 +203,14 @@ async fn main() {
     let token = std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN");
     let intents = serenity::GatewayIntents::non_privileged() | serenity::GatewayIntents::GUILD_MEMBERS | serenity::GatewayIntents::GUILD_MESSAGES | serenity::GatewayIntents::MESSAGE_CONTENT | serenity::GatewayIntents::GUILD_VOICE_STATES;
 
+    // Set up the database before connecting so a bad schema stops the bot from starting
+    let data = Data::init().await;
+
     let framework = poise::Framework::builder()
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data::init().await)
+                Ok(data)
             })
         })
         .options(poise::FrameworkOptions {
@@ -292,6 +229,8 @@ async fn main() {
                 vctracker::vctracker(),
                 vctracker::vctop(),
                 quotes::addquote(),
+                quotes::addconversation(),
+                quotes::quote_message(),
                 quotes::quote(),
                 quotes::delquote(),
                 quotes::setquoterole(),
@@ -303,6 +242,8 @@ async fn main() {
                 settings::settings(),
                 boost::boost(),
                 bot_management::set_bot_gif(),
+                bot_management::migration_status(),
+                bot_management::jobs(),
 
                 minigames::bomb::bomb(),
                 minigames::rockpaperscisso::rps(),
@@ -321,10 +262,13 @@ async fn main() {
                             ctx.command().name);
                     }
 
-                    user_table_check(ctx, ctx.author()).await;
+                    if let Err(e) = user_table_check(ctx, ctx.author()).await {
+                        error!("[ USER ] An error occurred checking the user table: {e:?}");
+                    }
                 })
             },
             event_handler: |ctx, event, framework, data| Box::pin(listener(ctx, event, framework, data)),
+            on_error: |error| Box::pin(error::on_error(error)),
             ..Default::default()
         })
         .build();
@@ -1,21 +1,142 @@
 use crate::{Data, Context, Error};
-use crate::customgifs::{grab_custom_gifs, GIFType, GIFDBQueryType};
+use crate::storage::Storage;
+use crate::storage::birthdays::ListedBirthday;
+use crate::storage::guild_settings::BirthdayGuild;
+use crate::pagination::{paginate, split_into_pages};
+use crate::customgifs::{grab_custom_gifs, CustomGif, GIFType, GIFDBQueryType};
 use poise::serenity_prelude as serenity;
-use chrono::Utc;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, TimeZone, Timelike, Utc};
 use rand::{thread_rng, Rng};
+use std::collections::HashSet;
+use tracing::{warn, error};
 
 //--------------------
 // Data
 //--------------------
 #[derive(sqlx::FromRow, Debug)]
 pub struct Birthday {
-    guild_id: u64,
-    user_id: u64,
-    birthday: u8,
-    birthmonth: u8,
-    nickname: Option<String>
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub birthday: u8,
+    pub birthmonth: u8,
+    pub birthyear: Option<u16>,
+    pub nickname: Option<String>,
+    pub private: bool,
+    pub show_age: bool,
+}
+
+impl Birthday {
+    // Date the birthday is celebrated on in a given year. None if the stored date doesn't exist.
+    pub fn date_in_year(&self, year: i32, leap_day: LeapDayPolicy) -> Option<NaiveDate> {
+        // Feb 29 birthdays move to the guild's chosen day when the year doesn't have one
+        if self.birthmonth == 2 && self.birthday == 29 && NaiveDate::from_ymd_opt(year, 2, 29).is_none() {
+            return match leap_day {
+                LeapDayPolicy::Feb28 => NaiveDate::from_ymd_opt(year, 2, 28),
+                LeapDayPolicy::Mar1 => NaiveDate::from_ymd_opt(year, 3, 1),
+            };
+        }
+
+        NaiveDate::from_ymd_opt(year, self.birthmonth as u32, self.birthday as u32)
+    }
+
+    // The next time the birthday is celebrated, counting today
+    pub fn next_date(&self, today: NaiveDate, leap_day: LeapDayPolicy) -> Option<NaiveDate> {
+        match self.date_in_year(today.year(), leap_day) {
+            Some(date) if date >= today => Some(date),
+            _ => self.date_in_year(today.year() + 1, leap_day)
+        }
+    }
+
+    // Age reached on the birthday celebrated in `year`, only if the user chose to show it
+    pub fn age_in_year(&self, year: i32) -> Option<i32> {
+        match self.birthyear {
+            Some(birthyear) if self.show_age => Some(year - birthyear as i32),
+            _ => None
+        }
+    }
+}
+
+// When to celebrate February 29 birthdays in years without one
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Debug)]
+pub enum LeapDayPolicy {
+    #[name = "February 28"]
+    Feb28,
+    #[name = "March 1"]
+    Mar1,
+}
+
+impl std::fmt::Display for LeapDayPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LeapDayPolicy::Feb28 => write!(f, "February 28"),
+            LeapDayPolicy::Mar1 => write!(f, "March 1")
+        }
+    }
+}
+
+impl LeapDayPolicy {
+    pub fn as_db(&self) -> &'static str {
+        match self {
+            LeapDayPolicy::Feb28 => "feb28",
+            LeapDayPolicy::Mar1 => "mar1",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "mar1" => LeapDayPolicy::Mar1,
+            _ => LeapDayPolicy::Feb28,
+        }
+    }
+}
+
+// Who gets pinged when a birthday is announced
+#[derive(poise::ChoiceParameter, Clone, Copy, PartialEq, Debug)]
+pub enum BirthdayMention {
+    #[name = "Everyone"]
+    Everyone,
+    #[name = "A role"]
+    Role,
+    #[name = "The birthday person"]
+    Person,
+    #[name = "Nobody"]
+    Nobody,
+}
+
+impl std::fmt::Display for BirthdayMention {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BirthdayMention::Everyone => write!(f, "everyone"),
+            BirthdayMention::Role => write!(f, "a role"),
+            BirthdayMention::Person => write!(f, "the birthday person"),
+            BirthdayMention::Nobody => write!(f, "nobody")
+        }
+    }
+}
+
+impl BirthdayMention {
+    pub fn as_db(&self) -> &'static str {
+        match self {
+            BirthdayMention::Everyone => "everyone",
+            BirthdayMention::Role => "role",
+            BirthdayMention::Person => "person",
+            BirthdayMention::Nobody => "none",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "role" => BirthdayMention::Role,
+            "person" => BirthdayMention::Person,
+            "none" => BirthdayMention::Nobody,
+            _ => BirthdayMention::Everyone,
+        }
+    }
 }
 
+// Placeholders that can be used in a custom announcement message
+const MESSAGE_PLACEHOLDERS: [&str; 4] = ["{name}", "{mention}", "{age}", "{server}"];
+
 const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
 
 //--------------------
@@ -25,12 +146,9 @@ const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "Jun
 async fn bday_channel_check(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    let settings = sqlx::query!("SELECT birthday_channel FROM guild_settings WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let birthday_channel = ctx.data().database.get_birthday_channel(guild_id).await?;
 
-    match settings.birthday_channel {
+    match birthday_channel {
         Some(_) => Ok(true),
         None => {
             ctx.send(poise::CreateReply::default()
@@ -45,25 +163,488 @@ async fn bday_channel_check(ctx: Context<'_>) -> Result<bool, Error> {
 // Determine which username to use
 async fn determine_username (
     ctx: &serenity::Context,
-    database: &sqlx::MySqlPool,
+    database: &Storage,
     bday_nick: Option<String>,
     guild_id: u64,
     user_id: u64
-) -> String {
+) -> Result<String, Error> {
     if bday_nick.is_none() {
         // Try to grab from user row
-        let user_display_name = sqlx::query!("SELECT display_name FROM users WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-            .fetch_optional(database)
-            .await
-            .unwrap();
+        let user_display_name = database.get_display_name(guild_id, user_id).await?;
 
         match user_display_name {
-            Some(record) => return record.display_name,
-            None => return serenity::UserId::new(user_id).to_user(&ctx).await.unwrap().display_name().to_string()
+            Some(display_name) => return Ok(display_name),
+            None => return Ok(serenity::UserId::new(user_id).to_user(&ctx).await?.display_name().to_string())
+        }
+    }
+
+    Ok(bday_nick.unwrap())
+}
+
+// IDs of every guild member with a role, fetched 1000 members at a time
+async fn members_with_role(ctx: Context<'_>, role_id: serenity::RoleId) -> Result<HashSet<u64>, Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let mut member_ids = HashSet::new();
+    let mut after = None;
+
+    loop {
+        let members = guild_id.members(ctx, Some(1000), after).await?;
+
+        member_ids.extend(members.iter()
+            .filter(|m| m.roles.contains(&role_id))
+            .map(|m| m.user.id.get()));
+
+        if members.len() < 1000 {
+            break;
+        }
+        after = members.last().map(|m| m.user.id);
+    }
+
+    Ok(member_ids)
+}
+
+fn format_birthday_line(i: usize, listed: &ListedBirthday) -> String {
+    let birthday = &listed.birthday;
+
+    // Members who never used the bot have no stored name, a mention still shows who they are
+    let username = birthday.nickname.clone()
+        .or(listed.display_name.clone())
+        .unwrap_or(format!("<@{}>", birthday.user_id));
+
+    // Year is only shown to members who opted in to showing their age
+    let year = match birthday.birthyear {
+        Some(year) if birthday.show_age => format!("/{year}"),
+        _ => String::new()
+    };
+
+    format!("- **[{}]** {} - ID: {} - Birthday: {}/{}{year}\n",
+        i + 1,
+        username,
+        birthday.user_id,
+        birthday.birthday,
+        birthday.birthmonth)
+}
+
+// Blank nicknames are stored as no nickname so the username is used instead
+fn clean_nickname(nickname: Option<String>) -> Option<String> {
+    nickname.filter(|n| !n.trim().is_empty())
+}
+
+// Make sure the date exists on the calendar. Feb 29 is allowed since it exists in leap years.
+fn validate_birthday(month: u8, day: u8, year: Option<u16>) -> Result<(), Error> {
+    let date = match NaiveDate::from_ymd_opt(year.map_or(2000, |y| y as i32), month as u32, day as u32) {
+        Some(d) => d,
+        None => return Err(format!("{} {day}{} isn't a real date!", MONTHS[month as usize - 1], year.map_or(String::new(), |y| format!(", {y}"))).into())
+    };
+
+    if year.is_some() && date > Utc::now().date_naive() {
+        return Err("Your birthday can't be in the future!".into());
+    }
+
+    Ok(())
+}
+
+// Any {placeholder} in an announcement template that isn't supported
+fn unknown_placeholders(template: &str) -> Vec<&str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+
+        let placeholder = &rest[start..=start + end];
+        if !MESSAGE_PLACEHOLDERS.contains(&placeholder) {
+            unknown.push(placeholder);
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    unknown
+}
+
+// Resolve the [optional sections] of a template that contain {age}. They're kept without their brackets if the age is known
+// and left out entirely otherwise. Square brackets without {age} in them are left alone.
+fn resolve_age_sections(template: &str, age_known: bool) -> String {
+    let mut resolved = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('[') {
+        resolved.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        match rest.find(']') {
+            Some(end) if rest[..end].contains("{age}") => {
+                if age_known {
+                    resolved.push_str(&rest[1..end]);
+                }
+                rest = &rest[end + 1..];
+            },
+            _ => {
+                resolved.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    resolved.push_str(rest);
+
+    resolved
+}
+
+// {age} outside of an optional section, which would be left blank for birthdays without a year
+fn has_bare_age(template: &str) -> bool {
+    resolve_age_sections(template, false).contains("{age}")
+}
+
+// Fill in an announcement template. Done in one pass so names containing placeholders are left alone.
+fn render_birthday_message(template: &str, username: &str, user_id: u64, age: Option<i32>, server_name: &str) -> String {
+    let template = resolve_age_sections(template, age.is_some());
+    let mut rendered = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest.find('}').map_or("", |end| &rest[..=end]);
+        let value = match placeholder {
+            "{name}" => username.to_string(),
+            "{mention}" => format!("<@{user_id}>"),
+            "{age}" => age.map_or(String::new(), |a| a.to_string()),
+            "{server}" => server_name.to_string(),
+            _ => {
+                rendered.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+
+        rendered.push_str(&value);
+        rest = &rest[placeholder.len()..];
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+// Message content pinging whoever the guild chose. None if nobody should be pinged.
+fn announcement_mention(guild: &BirthdayGuild, user_ids: &[u64]) -> Option<String> {
+    match guild.mention() {
+        BirthdayMention::Everyone => Some(String::from("@everyone :birthday:")),
+        BirthdayMention::Role => guild.birthday_mention_role.map(|r| format!("<@&{r}> :birthday:")),
+        BirthdayMention::Person => {
+            let mentions: Vec<String> = user_ids.iter().map(|u| format!("<@{u}>")).collect();
+            Some(format!("{} :birthday:", mentions.join(" ")))
+        },
+        BirthdayMention::Nobody => None
+    }
+}
+
+// Footer for custom messages posted after the day has passed
+fn belated_footer(date: NaiveDate) -> serenity::CreateEmbedFooter {
+    serenity::CreateEmbedFooter::new(format!("Sorry we're late, this birthday was on {} {}!", MONTHS[date.month0() as usize], date.day()))
+}
+
+// Announcement embed for one birthday, using the guild's custom message if it has one
+fn build_announcement_embed(
+    guild: &BirthdayGuild,
+    birthday: &Birthday,
+    username: &str,
+    server_name: &str,
+    date: NaiveDate,
+    belated: bool
+) -> serenity::CreateEmbed {
+    let age = birthday.age_in_year(date.year());
+    let mut embed = serenity::CreateEmbed::new()
+        .colour(0xFF0095);
+
+    match &guild.birthday_message {
+        Some(template) => {
+            embed = embed.description(render_birthday_message(template, username, birthday.user_id, age, server_name));
+
+            if belated {
+                embed = embed.footer(belated_footer(date));
+            }
+        },
+        None => {
+            let bday_msg = if belated {
+                format!("Happy belated birthday, {username}! :birthday: Sorry we missed it, we hope you had a great day!")
+            } else {
+                format!("Happy birthday, {username}! :birthday: We hope you have a great day!")
+            };
+            embed = embed.title(bday_msg);
+
+            if let Some(age) = age {
+                let age_msg = if belated { format!("{username} turned {age}!") } else { format!("{username} turns {age} today!") };
+                embed = embed.description(age_msg);
+            }
+        }
+    }
+
+    embed
+}
+
+// One announcement embed for everyone celebrating on the same day
+fn build_combined_announcement_embed(
+    guild: &BirthdayGuild,
+    birthdays: &[(&Birthday, String)],
+    server_name: &str,
+    date: NaiveDate,
+    belated: bool
+) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::new()
+        .colour(0xFF0095);
+
+    match &guild.birthday_message {
+        // The custom message is repeated for each person
+        Some(template) => {
+            let messages: Vec<String> = birthdays.iter()
+                .map(|(b, username)| render_birthday_message(template, username, b.user_id, b.age_in_year(date.year()), server_name))
+                .collect();
+            embed = embed.description(messages.join("\n\n"));
+
+            if belated {
+                embed = embed.footer(belated_footer(date));
+            }
+        },
+        None => {
+            let bday_msg = if belated {
+                format!("Happy belated birthday to all {} of you! :birthday: Sorry we missed it, we hope you had a great day!", birthdays.len())
+            } else {
+                format!("Happy birthday to all {} of you! :birthday: We hope you have a great day!", birthdays.len())
+            };
+
+            let mut embed_desc = String::new();
+            for (birthday, username) in birthdays {
+                let line = match birthday.age_in_year(date.year()) {
+                    Some(age) if belated => format!("- **{username}** turned {age}!"),
+                    Some(age) => format!("- **{username}** turns {age} today!"),
+                    None => format!("- **{username}**")
+                };
+                embed_desc = format!("{embed_desc}\n{line}");
+            }
+
+            embed = embed.title(bday_msg).description(embed_desc);
+        }
+    }
+
+    embed
+}
+
+// Pick one of the guild's birthday GIFs at random, empty if it has none
+fn pick_random_gif(gifs: &[CustomGif]) -> &str {
+    if gifs.is_empty() {
+        return "";
+    }
+
+    let mut rng = thread_rng();
+    &gifs[rng.gen_range(0..gifs.len())].gif_url
+}
+
+// Every birthday in the guild that isn't private, paired with the name to show for it
+async fn public_birthdays(ctx: Context<'_>, guild_id: u64) -> Result<Vec<(Birthday, String)>, Error> {
+    let birthdays = ctx.data().database.list_birthdays(guild_id, None).await?;
+    let mut named_birthdays = Vec::new();
+
+    for birthday in birthdays.into_iter().filter(|b| !b.private) {
+        let username = determine_username(ctx.serenity_context(), &ctx.data().database, birthday.nickname.clone(), guild_id, birthday.user_id).await?;
+        named_birthdays.push((birthday, username));
+    }
+
+    Ok(named_birthdays)
+}
+
+// Build a month grid with the days that have birthdays marked
+fn build_calendar_embed(
+    year: i32,
+    month: u32,
+    birthdays: &[(Birthday, String)],
+    leap_day: LeapDayPolicy
+) -> serenity::CreateEmbed {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let days_in_month = (first_day + Months::new(1)).pred_opt().unwrap().day();
+
+    // Birthdays celebrated this month, including Feb 29 birthdays moved by the leap day policy
+    let mut month_birthdays: Vec<(u32, &str)> = birthdays.iter()
+        .filter_map(|(b, name)| b.date_in_year(year, leap_day)
+            .filter(|d| d.month() == month)
+            .map(|d| (d.day(), name.as_str())))
+        .collect();
+    month_birthdays.sort();
+
+    let offset = first_day.weekday().num_days_from_monday();
+    let mut grid = format!("Mo  Tu  We  Th  Fr  Sa  Su\n{}", "    ".repeat(offset as usize));
+
+    for day in 1..=days_in_month {
+        let marker = if month_birthdays.iter().any(|(d, _)| *d == day) { '*' } else { ' ' };
+        grid = format!("{grid}{day:>2}{marker} ");
+
+        if (offset + day).is_multiple_of(7) {
+            grid.push('\n');
+        }
+    }
+
+    let mut embed_desc = format!("```\n{}\n```", grid.trim_end());
+    if month_birthdays.is_empty() {
+        embed_desc = format!("{embed_desc}\nNo birthdays this month!");
+    }
+    for (day, name) in month_birthdays {
+        embed_desc = format!("{embed_desc}\n- **{day}** - {name}");
+    }
+
+    serenity::CreateEmbed::new()
+        .colour(0xFFC60A)
+        .title(format!("{} {year}", MONTHS[month as usize - 1]))
+        .description(embed_desc)
+        .footer(serenity::CreateEmbedFooter::new("* marks a birthday"))
+}
+
+// Split a CSV line into trimmed fields. Quoted fields may contain commas and "" for a literal quote.
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c)
+        }
+    }
+    fields.push(field);
+
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+// The first row may name the columns instead of holding a birthday
+fn is_header_row(fields: &[String]) -> bool {
+    fields[0].parse::<u64>().is_err()
+}
+
+// Quote a CSV field if it would otherwise break the row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Parse one imported row of user_id, month, day, nickname. The error is the reason shown in the import report.
+fn parse_import_row(guild_id: u64, fields: &[String]) -> Result<Birthday, String> {
+    if fields.len() < 3 || fields.len() > 4 {
+        return Err(format!("Expected 3 or 4 columns but found {}", fields.len()));
+    }
+
+    let user_id = match fields[0].parse::<u64>() {
+        Ok(id) if id > 0 => id,
+        _ => return Err(format!("`{}` isn't a valid user ID", fields[0]))
+    };
+    let month = match fields[1].parse::<u8>() {
+        Ok(m) if (1..=12).contains(&m) => m,
+        _ => return Err(format!("`{}` isn't a valid month", fields[1]))
+    };
+    let day = match fields[2].parse::<u8>() {
+        Ok(d) if (1..=31).contains(&d) => d,
+        _ => return Err(format!("`{}` isn't a valid day", fields[2]))
+    };
+    validate_birthday(month, day, None).map_err(|e| e.user_message())?;
+
+    let nickname = fields.get(3).filter(|n| !n.is_empty()).cloned();
+    if nickname.as_ref().is_some_and(|n| n.chars().count() > 30) {
+        return Err(String::from("Nicknames can be up to 30 characters"));
+    }
+
+    Ok(Birthday {
+        guild_id,
+        user_id,
+        birthday: day,
+        birthmonth: month,
+        birthyear: None,
+        nickname,
+        private: false,
+        show_age: false,
+    })
+}
+
+// Escape text for an iCalendar property value. Carriage returns can't appear raw, so every line break becomes \n.
+fn ics_text(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', "\\n")
+}
+
+// Fold a content line so no physical line is longer than 75 octets. Continuations start with a space.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
         }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+// Build an .ics calendar with a yearly all-day event for each birthday
+fn build_birthday_calendar(calendar_name: &str, birthdays: &[(Birthday, String)], leap_day: LeapDayPolicy) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//AmethystBot//Birthdays//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+        String::from("METHOD:PUBLISH"),
+        format!("X-WR-CALNAME:{}", ics_text(calendar_name)),
+    ];
+
+    for (birthday, username) in birthdays {
+        // Feb 29 birthdays follow the guild's leap day policy in other years.
+        // The last day of February, or the 60th day of the year which is Feb 29 or Mar 1.
+        let rule = if birthday.birthmonth == 2 && birthday.birthday == 29 {
+            match leap_day {
+                LeapDayPolicy::Feb28 => "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1",
+                LeapDayPolicy::Mar1 => "FREQ=YEARLY;BYYEARDAY=60"
+            }
+        } else {
+            "FREQ=YEARLY"
+        };
+
+        // Birth years are never shared, 2000 is a leap year so every date has a valid first occurrence
+        let start = NaiveDate::from_ymd_opt(2000, birthday.birthmonth as u32, birthday.birthday as u32).unwrap();
+        let end = start + Duration::days(1);
+
+        lines.extend([
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}-{}@amethystbot", birthday.guild_id, birthday.user_id),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            format!("RRULE:{rule}"),
+            format!("SUMMARY:{}", ics_text(&format!("{username}'s Birthday"))),
+            String::from("TRANSP:TRANSPARENT"),
+            String::from("END:VEVENT"),
+        ]);
     }
+    lines.push(String::from("END:VCALENDAR"));
 
-    bday_nick.unwrap()
+    lines.iter().map(|line| format!("{}\r\n", fold_ics_line(line))).collect()
 }
 
 //--------------------
@@ -72,12 +653,96 @@ async fn determine_username (
 #[poise::command(
     slash_command,
     guild_only,
-    subcommands("add", "remove", "edit", "setchannel", "info", "setrole", "list")
+    subcommands("set", "forget", "add", "remove", "edit", "setchannel", "settime", "setbelated", "setleapday", "info", "setrole", "list", "upcoming", "calendar", "import", "export", "setmention", "setmessage", "setcombine", "preview", "reminders")
 )]
 pub async fn bday(_: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Save your own birthday
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Birthmonth."]
+    #[min = 1_u8]
+    #[max = 12_u8] month: u8,
+    #[description = "Birthday."]
+    #[min = 1_u8]
+    #[max = 31_u8] day: u8,
+    #[description = "Birth year, used to show your age."]
+    #[min = 1900_u16]
+    #[max = 9999_u16] year: Option<u16>,
+    #[description = "Remove your saved birth year."] clear_year: Option<bool>,
+    #[description = "A nickname to use in your birthday announcement."]
+    #[max_length = 30] nickname: Option<String>,
+    #[description = "Remove your saved nickname."] clear_nickname: Option<bool>,
+    #[description = "Hide your birthday from the birthday list."] private: Option<bool>,
+    #[description = "Show your age in announcements. Requires a birth year."] show_age: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user_id = ctx.author().id.get();
+
+    // Keep any existing choices unless they're being changed
+    let existing = ctx.data().database.get_birthday(guild_id, user_id).await?;
+    let birthday = Birthday {
+        guild_id,
+        user_id,
+        birthday: day,
+        birthmonth: month,
+        birthyear: match clear_year {
+            Some(true) => None,
+            _ => year.or(existing.as_ref().and_then(|b| b.birthyear)),
+        },
+        nickname: match clear_nickname {
+            Some(true) => None,
+            _ => clean_nickname(nickname).or(existing.as_ref().and_then(|b| b.nickname.clone())),
+        },
+        private: private.unwrap_or(existing.as_ref().is_some_and(|b| b.private)),
+        show_age: show_age.unwrap_or(existing.as_ref().is_some_and(|b| b.show_age)),
+    };
+
+    validate_birthday(birthday.birthmonth, birthday.birthday, birthday.birthyear)?;
+
+    if existing.is_some() {
+        ctx.data().database.update_birthday(&birthday).await?;
+    } else {
+        ctx.data().database.insert_birthday(&birthday).await?;
+    }
+
+    let privacy_msg = if birthday.private { " It is hidden from the birthday list." } else { "" };
+    let age_msg = if birthday.show_age && birthday.birthyear.is_some() { " Your age will be shown in announcements." } else { "" };
+    ctx.say(format!("Saved your birthday as {} {day}!{privacy_msg}{age_msg}", MONTHS[month as usize - 1])).await?;
+
+    Ok(())
+}
+
+/// Remove your own birthday
+#[poise::command(
+    slash_command,
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn forget(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user_id = ctx.author().id.get();
+
+    if ctx.data().database.get_birthday(guild_id, user_id).await?.is_none() {
+        return Err("You don't have a birthday saved on this server!".into());
+    }
+
+    ctx.data().database.delete_birthday(guild_id, user_id).await?;
+
+    ctx.say("Your birthday has been removed from this server!").await?;
+
+    Ok(())
+}
+
 /// Add a user's birthday
 #[poise::command(
     slash_command,
@@ -93,6 +758,9 @@ pub async fn add(
     #[description = "Birthday."]
     #[min = 1_u8]
     #[max = 31_u8] day: u8,
+    #[description = "Birth year. Age is only shown if the user turns it on with /bday set."]
+    #[min = 1900_u16]
+    #[max = 9999_u16] year: Option<u16>,
     #[description = "A nickname for the user."]
     #[max_length = 30] nickname: Option<String>,
 ) -> Result<(), Error> {
@@ -101,22 +769,19 @@ pub async fn add(
         user_id: user.id.get(),
         birthday: day,
         birthmonth: month,
-        nickname,
+        birthyear: year,
+        nickname: clean_nickname(nickname),
+        private: false,
+        show_age: false,
     };
 
-    let count = sqlx::query!("SELECT COUNT(user_id) AS count FROM birthday WHERE guild_id = ? AND user_id = ?", birthday.guild_id, birthday.user_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    validate_birthday(birthday.birthmonth, birthday.birthday, birthday.birthyear)?;
 
-    if count.count != 0 {
+    if ctx.data().database.get_birthday(birthday.guild_id, birthday.user_id).await?.is_some() {
         return Err(format!("{}'s birthday is already saved!", user.name).into());
     }
 
-    sqlx::query!("INSERT INTO birthday (guild_id, user_id, birthday, birthmonth, nickname) VALUES (?, ?, ?, ?, ?)", birthday.guild_id, birthday.user_id, birthday.birthday, birthday.birthmonth, birthday.nickname)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.insert_birthday(&birthday).await?;
 
     ctx.say(format!("Successfully added {}'s birthday!", user.name)).await?;
 
@@ -150,30 +815,20 @@ pub async fn remove(
         }
     };
 
-    let count = sqlx::query!("SELECT COUNT(user_id) AS count FROM birthday WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
-
-    if count.count == 0 {
-        let msg = if user.is_some() {
-            format!("No birthday found for {}!", user.unwrap().name)
-        } else {
-            format!("No birthday found matching ID: {user_id}")
+    if ctx.data().database.get_birthday(guild_id, user_id).await?.is_none() {
+        let msg = match &user {
+            Some(user) => format!("No birthday found for {}!", user.name),
+            None => format!("No birthday found matching ID: {user_id}")
         };
 
         return Err(msg.into());
     }
 
-    sqlx::query!("DELETE FROM birthday WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.delete_birthday(guild_id, user_id).await?;
 
-    let msg = if user.is_some() {
-        format!("Removed {} from the birthday list!", user.unwrap().name)
-    } else {
-        format!("Removed user from the birthday list with id: {}!", user_id)
+    let msg = match user {
+        Some(user) => format!("Removed {} from the birthday list!", user.name),
+        None => format!("Removed user from the birthday list with id: {}!", user_id)
     };
     ctx.say(msg).await?;
 
@@ -186,6 +841,7 @@ pub async fn remove(
     check = "bday_channel_check",
     required_permissions = "MANAGE_CHANNELS"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn edit (
     ctx: Context<'_>,
     #[description = "User to edit information."] user: serenity::User,
@@ -195,35 +851,46 @@ pub async fn edit (
     #[description = "Birthday."]
     #[min = 1_u8]
     #[max = 31_u8] day: Option<u8>,
+    #[description = "Birth year."]
+    #[min = 1900_u16]
+    #[max = 9999_u16] year: Option<u16>,
+    #[description = "Remove the user's birth year."] clear_year: Option<bool>,
     #[description = "A nickname for the user."]
     #[max_length = 30] nickname: Option<String>,
+    #[description = "Remove the user's nickname."] clear_nickname: Option<bool>,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
     let user_id = user.id.get();
+    let clear_year = clear_year.unwrap_or(false);
+    let clear_nickname = clear_nickname.unwrap_or(false);
 
     // Validate information
-    let birthday_info = sqlx::query_as!(Birthday, "SELECT * FROM birthday WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_optional(&ctx.data().database)
-        .await
-        .unwrap();
+    let birthday_info = ctx.data().database.get_birthday(guild_id, user_id).await?;
 
     if birthday_info.is_none() {
         return Err(format!("{}'s birthday is not saved!", user.name).into());
     }
 
-    if month.is_none() && day.is_none() && nickname.is_none() {
+    if month.is_none() && day.is_none() && year.is_none() && nickname.is_none() && !clear_year && !clear_nickname {
         return Err("You must choose at least one option to edit!".into());
     }
 
     // Build and execute query
-    let birthmonth = month.unwrap_or(birthday_info.as_ref().unwrap().birthmonth);
-    let birthday = day.unwrap_or(birthday_info.as_ref().unwrap().birthday);
-    let nickname = nickname.unwrap_or(birthday_info.unwrap().nickname.unwrap_or(String::new()));
+    let birthday_info = birthday_info.unwrap();
+    let birthday = Birthday {
+        guild_id,
+        user_id,
+        birthmonth: month.unwrap_or(birthday_info.birthmonth),
+        birthday: day.unwrap_or(birthday_info.birthday),
+        birthyear: if clear_year { None } else { year.or(birthday_info.birthyear) },
+        nickname: if clear_nickname { None } else { clean_nickname(nickname).or(birthday_info.nickname) },
+        private: birthday_info.private,
+        show_age: birthday_info.show_age,
+    };
+
+    validate_birthday(birthday.birthmonth, birthday.birthday, birthday.birthyear)?;
 
-    sqlx::query!("UPDATE birthday SET birthmonth = ?, birthday = ?, nickname = ? WHERE guild_id = ? AND user_id = ?", birthmonth, birthday, nickname, guild_id, user_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.update_birthday(&birthday).await?;
 
     ctx.say(format!("{}, altered information for {}!", ctx.author(), user.name)).await?;
 
@@ -241,20 +908,15 @@ pub async fn info(
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
     let user_id = user.id.get();
-    let count = sqlx::query!("SELECT COUNT(user_id) AS count FROM birthday WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let birthday = match ctx.data().database.get_birthday(guild_id, user_id).await? {
+        Some(b) => b,
+        None => return Err(format!("{}'s birthday is not saved!", user.name).into())
+    };
 
-    if count.count == 0 {
-        return Err(format!("{}'s birthday is not saved!", user.name).into());
+    if birthday.private && user.id != ctx.author().id {
+        return Err(format!("{} has chosen to keep their birthday private!", user.name).into());
     }
 
-    let birthday = sqlx::query_as!(Birthday, "SELECT * FROM birthday WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
-
     let formatted_day = match birthday.birthday {
         1 => "1st".to_string(),
         2 => "2nd".to_string(),
@@ -266,12 +928,24 @@ pub async fn info(
         _ => format!("{}th", birthday.birthday)
     };
 
-    let nickname = if birthday.nickname.is_some() {
-        format!(" ({})", birthday.nickname.unwrap())
-    } else {
-        String::new()
+    let nickname = match &birthday.nickname {
+        Some(nickname) => format!(" ({nickname})"),
+        None => String::new()
     };
-    let msg = format!("{}{}'s birthday is on {} {}!", user.name, nickname, MONTHS[birthday.birthmonth as usize - 1], formatted_day);
+
+    // Only mention age if the user chose to show it
+    let guild = ctx.data().database.get_birthday_guild(guild_id).await?;
+    let today = Utc::now().with_timezone(&guild.tz()).date_naive();
+    let age = birthday.next_date(today, guild.leap_day())
+        .and_then(|next| birthday.age_in_year(next.year()).map(|age| (next, age)));
+
+    let age_msg = match age {
+        Some((next, age)) if next == today => format!(" They turn {age} today!"),
+        Some((_, age)) => format!(" They turn {age} on their next birthday."),
+        None => String::new()
+    };
+
+    let msg = format!("{}{}'s birthday is on {} {}!{age_msg}", user.name, nickname, MONTHS[birthday.birthmonth as usize - 1], formatted_day);
 
     ctx.say(msg).await?;
 
@@ -290,102 +964,716 @@ pub async fn setchannel(
     let channel_id = channel.id().get();
     let guild_id = ctx.guild_id().unwrap().get();
 
-    sqlx::query!("UPDATE guild_settings SET birthday_channel = ? WHERE guild_id = ?", channel_id, guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_birthday_channel(guild_id, Some(channel_id)).await?;
 
     ctx.say(format!("Now posting birthday announcements in {}!", channel)).await?;
 
     Ok(())
 }
 
-/// Set the role to give the birthday person
+/// Set the hour to send birthday announcements
 #[poise::command(
     slash_command,
     required_permissions = "MANAGE_CHANNELS",
     check = "bday_channel_check"
 )]
-pub async fn setrole(
+pub async fn settime(
     ctx: Context<'_>,
-    #[description = "Role to give the birthday person."] role: Option<serenity::Role>
+    #[description = "Hour of the day (0-23) in the server's timezone."]
+    #[min = 0_u8]
+    #[max = 23_u8] hour: u8
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    // Build/run query
-    let role_id: Option<u64> = role.as_ref().map(|r| r.id.get());
+    ctx.data().database.set_birthday_hour(guild_id, hour).await?;
+    let timezone = ctx.data().database.get_timezone(guild_id).await?;
 
-    sqlx::query!("UPDATE guild_settings SET birthday_role = ? WHERE guild_id = ?", role_id, guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.say(format!("Now posting birthday announcements at {hour:02}:00 ({})!", timezone.name())).await?;
 
-    // Message author
-    if role.is_none() {
-        ctx.say(format!("{}, no longer giving a role on a user's birthday!", ctx.author())).await?;
+    Ok(())
+}
+
+/// Set how late missed birthdays can still be announced
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check"
+)]
+pub async fn setbelated(
+    ctx: Context<'_>,
+    #[description = "Days after a missed birthday to still post a belated message. 0 to disable."]
+    #[min = 0_u8]
+    #[max = 30_u8] days: u8
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    ctx.data().database.set_birthday_belated_days(guild_id, days).await?;
+
+    if days == 0 {
+        ctx.say(format!("{}, no longer posting belated birthday messages!", ctx.author())).await?;
     } else {
-        ctx.say(format!("{}. now giving the {} role on birthdays!", ctx.author(), role.unwrap().name)).await?;
+        ctx.say(format!("{}, now posting belated messages for birthdays missed in the last {days} day(s)!", ctx.author())).await?;
     }
 
     Ok(())
 }
 
-/// List all birthdays on the server
+/// Set when February 29 birthdays are celebrated in non-leap years
 #[poise::command(
     slash_command,
     required_permissions = "MANAGE_CHANNELS",
-    check = "bday_channel_check",
+    check = "bday_channel_check"
 )]
-pub async fn list(
+pub async fn setleapday(
     ctx: Context<'_>,
-
-    #[min = 1_u8]
-    #[max = 12_u8] 
-    #[description = "Optionally only list birthdays for a specific month."] month: Option<u8>
+    #[description = "Day to celebrate February 29 birthdays on in non-leap years."] policy: LeapDayPolicy
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    // Fetch any birthdays if any
-    let month_query = if month.is_some() {
-        format!(" AND birthmonth = {}", month.unwrap())
-    } else {
-        String::new()
-    };
-
-    let query = format!("SELECT * FROM birthday WHERE guild_id = {guild_id}{month_query} ORDER BY birthmonth, birthday");
+    ctx.data().database.set_leap_day_policy(guild_id, policy).await?;
 
-    let birthdays: Vec<Birthday> = sqlx::query_as(&query)
-        .fetch_all(&ctx.data().database)
-        .await
-        .unwrap();
-    
-    if birthdays.is_empty() {
-        return Err("No birthdays found for this server!".into());
-    }
+    ctx.say(format!("{}, February 29 birthdays will be celebrated on {} in non-leap years!", ctx.author(), policy)).await?;
 
-    // Build birthday list embed
-    let mut birthday_embed = serenity::CreateEmbed::new()
-        .colour(0xFFC60A)
-        .title("User Birthdays");
+    Ok(())
+}
+
+/// Set the role to give the birthday person
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check"
+)]
+pub async fn setrole(
+    ctx: Context<'_>,
+    #[description = "Role to give the birthday person."] role: Option<serenity::Role>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    // Build/run query
+    let role_id: Option<u64> = role.as_ref().map(|r| r.id.get());
+
+    ctx.data().database.set_birthday_role(guild_id, role_id).await?;
+
+    // Message author
+    match role {
+        Some(role) => ctx.say(format!("{}. now giving the {} role on birthdays!", ctx.author(), role.name)).await?,
+        None => ctx.say(format!("{}, no longer giving a role on a user's birthday!", ctx.author())).await?
+    };
+
+    Ok(())
+}
+
+/// List all birthdays on the server
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check",
+)]
+pub async fn list(
+    ctx: Context<'_>,
+
+    #[min = 1_u8]
+    #[max = 12_u8] 
+    #[description = "Optionally only list birthdays for a specific month."] month: Option<u8>,
+    #[description = "Optionally only list members with this role."] role: Option<serenity::Role>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    // Fetch any birthdays if any, leaving out private ones
+    let mut birthdays: Vec<ListedBirthday> = ctx.data().database.list_birthdays_with_names(guild_id, month).await?
+        .into_iter()
+        .filter(|b| !b.birthday.private)
+        .collect();
+
+    // Every member has @everyone, so there's nothing to filter by
+    if let Some(role) = role.as_ref().filter(|r| r.id.get() != guild_id) {
+        let members = members_with_role(ctx, role.id).await?;
+        birthdays.retain(|b| members.contains(&b.birthday.user_id));
+    }
+
+    if birthdays.is_empty() {
+        return Err("No birthdays found for this server!".into());
+    }
+
+    let mut title = String::from("User Birthdays");
+    if let Some(month) = month {
+        title = format!("{title} - {}", MONTHS[month as usize - 1]);
+    }
+    if let Some(role) = &role {
+        title = format!("{title} - {}", role.name);
+    }
+
+    let pages = split_into_pages(&birthdays, 15, format_birthday_line);
+
+    paginate(ctx, &title, 0xFFC60A, &pages).await?;
+
+    Ok(())
+}
+
+/// List the birthdays coming up on the server
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    member_cooldown = 5
+)]
+pub async fn upcoming(
+    ctx: Context<'_>,
+    #[description = "How many days ahead to look. Defaults to 30."]
+    #[min = 1_u16]
+    #[max = 366_u16] days: Option<u16>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let days = days.unwrap_or(30);
+
+    let guild = ctx.data().database.get_birthday_guild(guild_id).await?;
+    let leap_day = guild.leap_day();
+    let today = Utc::now().with_timezone(&guild.tz()).date_naive();
+    let last_day = today + Duration::days(days as i64);
+
+    // Next occurrence of each birthday, wrapping around into next year
+    let mut upcoming: Vec<(NaiveDate, Birthday, String)> = public_birthdays(ctx, guild_id).await?
+        .into_iter()
+        .filter_map(|(b, name)| b.next_date(today, leap_day).map(|d| (d, b, name)))
+        .filter(|(d, _, _)| *d <= last_day)
+        .collect();
+    upcoming.sort_by_key(|(d, _, _)| *d);
+
+    if upcoming.is_empty() {
+        return Err(format!("No birthdays in the next {days} days!").into());
+    }
 
     let mut embed_desc = String::new();
+    for (i, (date, birthday, username)) in upcoming.iter().enumerate() {
+        let when = match (*date - today).num_days() {
+            0 => String::from("Today!"),
+            1 => String::from("Tomorrow"),
+            n => format!("In {n} days")
+        };
+        let age = match birthday.age_in_year(date.year()) {
+            Some(age) => format!(" (turning {age})"),
+            None => String::new()
+        };
+
+        let line = format!("\n- **{} {}** - {username}{age} - {when}", MONTHS[date.month0() as usize], date.day());
+
+        // Stay under Discord's embed description limit
+        if embed_desc.len() + line.len() > 4000 {
+            embed_desc = format!("{embed_desc}\n...and {} more", upcoming.len() - i);
+            break;
+        }
+        embed_desc = format!("{embed_desc}{line}");
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .colour(0xFFC60A)
+        .title(format!("Upcoming Birthdays - Next {days} Days"))
+        .description(embed_desc);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Show a calendar of the server's birthdays
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    member_cooldown = 5
+)]
+pub async fn calendar(
+    ctx: Context<'_>,
+    #[description = "Month to start on. Defaults to the current month."]
+    #[min = 1_u8]
+    #[max = 12_u8] month: Option<u8>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let guild = ctx.data().database.get_birthday_guild(guild_id).await?;
+    let leap_day = guild.leap_day();
+    let today = Utc::now().with_timezone(&guild.tz()).date_naive();
+
+    let birthdays = public_birthdays(ctx, guild_id).await?;
+    let mut year = today.year();
+    let mut month = month.map_or(today.month(), |m| m as u32);
+
+    // Create initial embed
+    let ctx_id = ctx.id();
+    let prev_id = format!("{ctx_id}prev");
+    let next_id = format!("{ctx_id}next");
+
+    let buttons: Vec<serenity::CreateButton> = vec![
+        serenity::CreateButton::new(&prev_id).label("Previous"),
+        serenity::CreateButton::new(&next_id).label("Next")
+    ];
+    let buttons = serenity::CreateActionRow::Buttons(buttons);
+
+    ctx.send(poise::CreateReply::default()
+        .embed(build_calendar_embed(year, month, &birthdays, leap_day))
+        .components(vec![buttons])
+    ).await?;
+
+    // Handle button interactions
+    while let Some(press) = serenity::collector::ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(1800))
+        .await {
+            if press.data.custom_id == prev_id {
+                if month == 1 { month = 12; year -= 1; } else { month -= 1; }
+            } else if press.data.custom_id == next_id {
+                if month == 12 { month = 1; year += 1; } else { month += 1; }
+            } else {
+                continue;
+            }
+
+            press.create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(build_calendar_embed(year, month, &birthdays, leap_day))
+                )
+            ).await?;
+    }
+
+    Ok(())
+}
+
+/// Import birthdays from a CSV file
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "CSV file with the columns user_id, month, day, nickname."] file: serenity::Attachment,
+    #[description = "Replace birthdays that are already saved. Defaults to false."] overwrite: Option<bool>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let overwrite = overwrite.unwrap_or(false);
+
+    if file.size > 1_000_000 {
+        return Err("That file is too big! CSV files can be up to 1MB.".into());
+    }
+
+    ctx.defer().await?;
+
+    let contents = match String::from_utf8(file.download().await?) {
+        Ok(c) => c,
+        Err(_) => return Err("That file isn't a valid CSV file!".into())
+    };
+
+    // Validate every row before touching the database
+    let mut birthdays = Vec::new();
+    let mut problems = Vec::new();
+    let mut seen_users = HashSet::new();
+
+    for (i, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+
+        // Skip the header row if there is one
+        if i == 0 && is_header_row(&fields) {
+            continue;
+        }
 
-    for (i, birthday) in birthdays.into_iter().enumerate() {
-        // Fetch username from UserID
-        let username = serenity::UserId::new(birthday.user_id).to_user(&ctx.serenity_context().http).await.unwrap().name;
+        match parse_import_row(guild_id, &fields) {
+            Ok(b) if !seen_users.insert(b.user_id) => problems.push(format!("Line {}: User {} is listed more than once", i + 1, b.user_id)),
+            Ok(b) => birthdays.push(b),
+            Err(e) => problems.push(format!("Line {}: {e}", i + 1))
+        }
+    }
+
+    if birthdays.is_empty() && problems.is_empty() {
+        return Err("That file doesn't have any birthdays in it!".into());
+    }
+
+    let (added, updated) = ctx.data().database.import_birthdays(&birthdays, overwrite).await?;
+    let already_saved = birthdays.len() as u32 - added - updated;
+
+    // Build the validation report
+    let mut embed_desc = format!("Added: **{added}**\nUpdated: **{updated}**\nAlready saved: **{already_saved}**\nRejected: **{}**", problems.len());
+
+    if already_saved > 0 {
+        embed_desc = format!("{embed_desc}\n\nRun the import again with `overwrite` to replace birthdays that are already saved.");
+    }
+
+    if !problems.is_empty() {
+        embed_desc = format!("{embed_desc}\n\n**Rejected rows**");
+
+        for (i, problem) in problems.iter().enumerate() {
+            // Stay under Discord's embed description limit
+            if embed_desc.len() + problem.len() > 4000 {
+                embed_desc = format!("{embed_desc}\n...and {} more", problems.len() - i);
+                break;
+            }
+            embed_desc = format!("{embed_desc}\n- {problem}");
+        }
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .colour(0xFFC60A)
+        .title("Birthday Import")
+        .description(embed_desc);
 
-        embed_desc = format!("{embed_desc}\n- **[{}]** {} - ID: {} - Birthday: {}/{}",
-            i + 1,
-            username,
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Export the server's birthdays as a CSV file and a calendar file
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    required_permissions = "MANAGE_CHANNELS",
+    ephemeral
+)]
+pub async fn export(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    ctx.defer_ephemeral().await?;
+
+    let birthdays = ctx.data().database.list_birthdays(guild_id, None).await?;
+    if birthdays.is_empty() {
+        return Err("No birthdays found for this server!".into());
+    }
+
+    // The CSV has every birthday in the same format /bday import takes
+    let mut csv = String::from("user_id,month,day,nickname\r\n");
+    for birthday in &birthdays {
+        csv = format!("{csv}{},{},{},{}\r\n",
             birthday.user_id,
+            birthday.birthmonth,
             birthday.birthday,
-            birthday.birthmonth);
+            csv_field(birthday.nickname.as_deref().unwrap_or("")));
     }
-    birthday_embed = birthday_embed.description(embed_desc);
 
-    // Send birthday list
+    // The calendar is meant to be shared with members, so private birthdays are left out
+    let leap_day = ctx.data().database.get_birthday_guild(guild_id).await?.leap_day();
+    let guild_name = ctx.guild().map_or(String::from("Server"), |g| g.name.clone());
+    let ics = build_birthday_calendar(&format!("{guild_name} Birthdays"), &public_birthdays(ctx, guild_id).await?, leap_day);
+
     ctx.send(poise::CreateReply::default()
-        .embed(birthday_embed)).await?;
+        .content(format!("Exported {} birthdays! Share `birthdays.ics` with members so they can add everyone's birthdays to their calendar. Private birthdays are only included in the CSV.", birthdays.len()))
+        .attachment(serenity::CreateAttachment::bytes(csv.into_bytes(), "birthdays.csv"))
+        .attachment(serenity::CreateAttachment::bytes(ics.into_bytes(), "birthdays.ics"))
+    ).await?;
+
+    Ok(())
+}
+
+/// Choose who gets pinged when a birthday is announced
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check"
+)]
+pub async fn setmention(
+    ctx: Context<'_>,
+    #[description = "Who to ping in birthday announcements."] mention: BirthdayMention,
+    #[description = "Role to ping. Required when pinging a role."] role: Option<serenity::Role>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let role = match mention {
+        BirthdayMention::Role if role.is_none() => return Err("You must choose a role to ping!".into()),
+        BirthdayMention::Role => role,
+        _ => None
+    };
+
+    ctx.data().database.set_birthday_mention(guild_id, mention, role.as_ref().map(|r| r.id.get())).await?;
+
+    // Name the role rather than mentioning it so this reply doesn't ping anyone
+    let target = match role {
+        Some(r) => format!("the {} role", r.name),
+        None => mention.to_string()
+    };
+    ctx.say(format!("{}, birthday announcements will now ping {target}!", ctx.author())).await?;
+
+    Ok(())
+}
+
+/// Set a custom birthday announcement message
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check"
+)]
+pub async fn setmessage(
+    ctx: Context<'_>,
+    #[description = "Announcement message. Supports {name}, {mention}, {server} and [optional text with {age}]."]
+    #[max_length = 1000] message: Option<String>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    if message.is_none() {
+        ctx.data().database.set_birthday_message(guild_id, None).await?;
+
+        ctx.say(format!("{}, birthday announcements will use the default message again!", ctx.author())).await?;
+
+        return Ok(());
+    }
+    let message = message.unwrap();
+
+    let unknown = unknown_placeholders(&message);
+    if !unknown.is_empty() {
+        return Err(format!("Unknown placeholders: {}. You can use {}.", unknown.join(", "), MESSAGE_PLACEHOLDERS.join(", ")).into());
+    }
+    if has_bare_age(&message) {
+        return Err("Put `{age}` in square brackets along with the words around it, like `[ They're turning {age}!]`. \
+            That part is left out for birthdays without a birth year or with the age hidden.".into());
+    }
+
+    ctx.data().database.set_birthday_message(guild_id, Some(&message)).await?;
+
+    ctx.say(format!("{}, birthday announcements will now say: ```{message}```Use `/bday preview` to see how it looks!", ctx.author())).await?;
+
+    Ok(())
+}
+
+/// Announce birthdays on the same day together in one message
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check"
+)]
+pub async fn setcombine(
+    ctx: Context<'_>,
+    #[description = "Combine birthdays on the same day into one announcement."] enabled: bool
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    ctx.data().database.set_birthday_combine(guild_id, enabled).await?;
+
+    if enabled {
+        ctx.say(format!("{}, birthdays on the same day will now be announced together!", ctx.author())).await?;
+    } else {
+        ctx.say(format!("{}, every birthday will now be announced separately!", ctx.author())).await?;
+    }
+
+    Ok(())
+}
+
+/// Preview what a birthday announcement will look like
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_CHANNELS",
+    check = "bday_channel_check",
+    ephemeral
+)]
+pub async fn preview(
+    ctx: Context<'_>,
+    #[description = "User to preview the announcement for. Defaults to you."] user: Option<serenity::User>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user = user.as_ref().unwrap_or(ctx.author());
+
+    let guild = ctx.data().database.get_birthday_guild(guild_id).await?;
+    let today = Utc::now().with_timezone(&guild.tz()).date_naive();
+
+    // Use their saved birthday so the age shows up like it will on the day, otherwise pretend it's today
+    let birthday = match ctx.data().database.get_birthday(guild_id, user.id.get()).await? {
+        Some(b) => b,
+        None => Birthday {
+            guild_id,
+            user_id: user.id.get(),
+            birthday: today.day() as u8,
+            birthmonth: today.month() as u8,
+            birthyear: None,
+            nickname: None,
+            private: false,
+            show_age: false,
+        }
+    };
+    let date = birthday.next_date(today, guild.leap_day()).unwrap_or(today);
+
+    let username = determine_username(ctx.serenity_context(), &ctx.data().database, birthday.nickname.clone(), guild_id, birthday.user_id).await?;
+    let server_name = ctx.guild().map_or(String::new(), |g| g.name.clone());
+    let birthday_gifs = grab_custom_gifs(&ctx.data().database, &GIFType::Birthday, guild_id, GIFDBQueryType::Normal).await?;
+
+    let embed = build_announcement_embed(&guild, &birthday, &username, &server_name, date, false)
+        .image(pick_random_gif(&birthday_gifs))
+        .thumbnail(user.face());
+
+    let mut reply = poise::CreateReply::default()
+        .embed(embed)
+        .allowed_mentions(serenity::CreateAllowedMentions::new());
+
+    if let Some(mention) = announcement_mention(&guild, &[birthday.user_id]) {
+        reply = reply.content(mention);
+    }
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Get a DM ahead of your friends' birthdays
+#[poise::command(
+    slash_command,
+    subcommands("enable", "disable", "reminders_add", "reminders_remove", "reminders_list")
+)]
+pub async fn reminders(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Turn on birthday reminder DMs
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn enable(
+    ctx: Context<'_>,
+    #[description = "How many days before a birthday to remind you."]
+    #[min = 1_u8]
+    #[max = 30_u8] days: u8
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user_id = ctx.author().id.get();
+
+    ctx.data().database.set_birthday_reminder_days(guild_id, user_id, Some(days)).await?;
+
+    let mut msg = format!("You'll now get a DM {days} day{} before the birthdays on your reminder list!", if days == 1 { "" } else { "s" });
+    if ctx.data().database.get_birthday_reminder_targets(guild_id, user_id).await?.is_empty() {
+        msg = format!("{msg} Add people to the list with `/bday reminders add`.");
+    }
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Turn off birthday reminder DMs
+#[poise::command(
+    slash_command,
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn disable(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    ctx.data().database.set_birthday_reminder_days(guild_id, ctx.author().id.get(), None).await?;
+
+    ctx.say("You'll no longer get birthday reminders. Your reminder list has been kept in case you turn them back on.").await?;
+
+    Ok(())
+}
+
+/// Add someone to your birthday reminder list
+#[poise::command(
+    slash_command,
+    rename = "add",
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn reminders_add(
+    ctx: Context<'_>,
+    #[description = "User to be reminded about."] user: serenity::User
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user_id = ctx.author().id.get();
+
+    if user.id.get() == user_id {
+        return Err("You probably don't need a reminder for your own birthday!".into());
+    }
+
+    // Private birthdays can't be added, the reminder would give the date away
+    match ctx.data().database.get_birthday(guild_id, user.id.get()).await? {
+        Some(b) if !b.private => {},
+        _ => return Err(format!("{} hasn't shared their birthday on this server!", user.name).into())
+    }
+
+    if !ctx.data().database.add_birthday_reminder(guild_id, user_id, user.id.get()).await? {
+        return Err(format!("{} is already on your reminder list!", user.name).into());
+    }
+
+    let msg = match ctx.data().database.get_birthday_reminder_days(guild_id, user_id).await? {
+        Some(days) => format!("Added {} to your reminder list! You'll get a DM {days} day{} before their birthday.", user.name, if days == 1 { "" } else { "s" }),
+        None => format!("Added {} to your reminder list! Turn reminders on with `/bday reminders enable` to start getting DMs.", user.name)
+    };
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// Remove someone from your birthday reminder list
+#[poise::command(
+    slash_command,
+    rename = "remove",
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn reminders_remove(
+    ctx: Context<'_>,
+    #[description = "User to stop being reminded about."] user: serenity::User
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    if !ctx.data().database.delete_birthday_reminder(guild_id, ctx.author().id.get(), user.id.get()).await? {
+        return Err(format!("{} isn't on your reminder list!", user.name).into());
+    }
+
+    ctx.say(format!("Removed {} from your reminder list!", user.name)).await?;
+
+    Ok(())
+}
+
+/// Show your birthday reminder list
+#[poise::command(
+    slash_command,
+    rename = "list",
+    check = "bday_channel_check",
+    member_cooldown = 5,
+    ephemeral
+)]
+pub async fn reminders_list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let user_id = ctx.author().id.get();
+
+    let targets = ctx.data().database.get_birthday_reminder_targets(guild_id, user_id).await?;
+    if targets.is_empty() {
+        return Err("Your reminder list is empty! Add people to it with `/bday reminders add`.".into());
+    }
+
+    let guild = ctx.data().database.get_birthday_guild(guild_id).await?;
+    let today = Utc::now().with_timezone(&guild.tz()).date_naive();
+
+    let header = match ctx.data().database.get_birthday_reminder_days(guild_id, user_id).await? {
+        Some(days) => format!("Reminding you **{days}** day{} before each birthday.\n", if days == 1 { "" } else { "s" }),
+        None => String::from("Reminders are turned off. Turn them on with `/bday reminders enable`.\n")
+    };
+
+    let mut lines = Vec::new();
+    for target in targets {
+        let birthday = ctx.data().database.get_birthday(guild_id, target).await?;
+
+        let line = match birthday {
+            Some(b) if !b.private => {
+                let username = determine_username(ctx.serenity_context(), &ctx.data().database, b.nickname.clone(), guild_id, target).await?;
+                match b.next_date(today, guild.leap_day()) {
+                    Some(date) => format!("**{username}** - {} {}", MONTHS[date.month0() as usize], date.day()),
+                    None => format!("**{username}**")
+                }
+            },
+            // Removed or made private since it was added
+            _ => format!("<@{target}> - Birthday no longer shared")
+        };
+
+        lines.push(line);
+    }
+
+    // Long lists are split up so they fit in an embed
+    let mut pages = split_into_pages(&lines, 15, |_, line| format!("- {line}\n"));
+    pages[0] = format!("{header}\n{}", pages[0]);
+
+    paginate(ctx, "Birthday Reminders", 0xFFC60A, &pages).await?;
 
     Ok(())
 }
@@ -393,82 +1681,539 @@ pub async fn list(
 //--------------------------
 // Birthday Checker
 //--------------------------
-pub async fn birthday_check(ctx: &serenity::Context, data: &Data) {
-    // Check the time (10 UTC, 2 Pacific)
+// Announce birthdays and hand out the birthday role. Run hourly by the scheduler.
+pub async fn birthday_check(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let registered_guild_channels = data.database.get_birthday_guilds().await?;
     let current_time = Utc::now();
 
-    if current_time.format("%H:%M:%S").to_string() == "10:00:00" {
-        let registered_guild_channels = sqlx::query!("SELECT guild_id, birthday_channel, birthday_role FROM guild_settings")
-            .fetch_all(&data.database)
-            .await
-            .unwrap();
+    let mut first_error = None;
 
-        let current_date = Utc::now().format("%m-%d").to_string();
-        let current_date: Vec<u8> = current_date.split('-').map(|i| i.parse::<u8>().unwrap()).collect();
+    // Loop the registered guilds
+    for guild in registered_guild_channels {
+        // Check for birthday channel; If none, skip everything
+        if guild.birthday_channel.is_none() {
+            continue;
+        }
 
-        // Loop the registered guilds
-        for guild in registered_guild_channels {
-            // Check for birthday channel; If none, skip everything
-            if guild.birthday_channel.is_none() {
-                continue;
+        // One guild failing shouldn't stop the rest from being announced
+        if let Err(e) = guild_birthday_check(ctx, data, &guild, current_time).await {
+            error!("[ BIRTHDAY ] An error occurred during the birthday check - Guild ID: {} - {e:?}", guild.guild_id);
+            first_error.get_or_insert(e);
+            continue;
+        }
+
+        // Reminders wait until the guild's announcements all went out. Failed ones are tried again next run.
+        if let Err(e) = guild_birthday_reminders(ctx, data, &guild, current_time).await {
+            error!("[ BIRTHDAY ] An error occurred sending birthday reminders - Guild ID: {} - {e:?}", guild.guild_id);
+        }
+    }
+
+    // Failing lets the scheduler retry the check. Guilds that finished return early and announced birthdays aren't sent again.
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(())
+    }
+}
+
+// Post every birthday since the guild's last announcement, including ones missed while the bot was down
+async fn guild_birthday_check(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &BirthdayGuild,
+    current_time: DateTime<Utc>
+) -> Result<(), Error> {
+    let local_time = current_time.with_timezone(&guild.tz());
+    let today = local_time.date_naive();
+    let is_announce_hour = local_time.hour() == guild.birthday_hour as u32;
+
+    // The latest day whose announcement time has already come
+    let due_date = if local_time.hour() >= guild.birthday_hour as u32 {
+        today
+    } else {
+        today.pred_opt().unwrap()
+    };
+
+    // Days that have already passed are only posted as belated within the guild's window
+    let oldest_date = today - Duration::days(guild.birthday_belated_days as i64);
+
+    let first_date = match guild.birthday_last_announced {
+        Some(last_announced) if last_announced >= due_date => return Ok(()),
+        Some(last_announced) => last_announced.succ_opt().unwrap().max(oldest_date),
+        // Never announced before, so don't guess at what was missed
+        None if is_announce_hour => due_date,
+        None => due_date.succ_opt().unwrap(),
+    };
+
+    let channel_id = serenity::ChannelId::new(guild.birthday_channel.unwrap());
+    let server_name = serenity::GuildId::new(guild.guild_id).name(ctx).unwrap_or_default();
+    let leap_day = guild.leap_day();
+    let guild_birthdays = data.database.list_birthdays(guild.guild_id, None).await?;
+    let birthday_gifs = grab_custom_gifs(&data.database, &GIFType::Birthday, guild.guild_id, GIFDBQueryType::Normal).await?;
+
+    // The last announced date only moves past days where every announcement went out
+    let mut delivered_through = first_date.pred_opt().unwrap();
+    let mut first_error = None;
+
+    for date in first_date.iter_days().take_while(|d| *d <= due_date) {
+        let belated = date < today;
+
+        // Announcements that went out before a failure are recorded so retries don't post them twice
+        let announced = data.database.get_announced_birthdays(guild.guild_id, date).await?;
+        let birthdays_on_date: Vec<&Birthday> = guild_birthdays.iter()
+            .filter(|b| is_birthday_on(b, date, leap_day) && !announced.contains(&b.user_id))
+            .collect();
+
+        // Shared birthdays go out as one message if the guild asked for it
+        if guild.birthday_combine && birthdays_on_date.len() > 1 {
+            let announced: Result<(), Error> = async {
+                let mut named_birthdays = Vec::new();
+                for birthday in &birthdays_on_date {
+                    let username = determine_username(ctx, &data.database, birthday.nickname.clone(), birthday.guild_id, birthday.user_id).await?;
+                    named_birthdays.push((*birthday, username));
+                }
+
+                let embed = build_combined_announcement_embed(guild, &named_birthdays, &server_name, date, belated)
+                    .image(pick_random_gif(&birthday_gifs));
+
+                let mut msg = serenity::CreateMessage::new()
+                    .embed(embed);
+
+                let user_ids: Vec<u64> = birthdays_on_date.iter().map(|b| b.user_id).collect();
+                if let Some(mention) = announcement_mention(guild, &user_ids) {
+                    msg = msg.content(mention);
+                }
+
+                channel_id.send_message(&ctx, msg).await?;
+                data.database.add_birthday_announcements(guild.guild_id, &user_ids, date).await?;
+                Ok(())
+            }.await;
+
+            if let Err(e) = announced {
+                error!("[ BIRTHDAY ] Unable to announce combined birthdays - Guild ID: {} - Date: {date} - {e:?}", guild.guild_id);
+                first_error.get_or_insert(e);
+            } else if first_error.is_none() {
+                delivered_through = date;
             }
-            let channel_id = serenity::ChannelId::new(guild.birthday_channel.unwrap());
-
-            let guild_birthdays = sqlx::query!("SELECT * FROM birthday WHERE guild_id = ?", guild.guild_id)
-                .fetch_all(&data.database)
-                .await
-                .unwrap();
-
-            let birthday_gifs = grab_custom_gifs(&data.database, &GIFType::Birthday, guild.guild_id, GIFDBQueryType::Normal).await;
-
-            for birthday in guild_birthdays {
-                if birthday.birthmonth == current_date[0] && birthday.birthday == current_date[1] {
-                    let username = determine_username(ctx, &data.database, birthday.nickname, birthday.guild_id, birthday.user_id).await;
-
-                    // Take care of the birthday message
-                    let bday_msg = format!("Happy birthday, {username}! :birthday: We hope you have a great day!");
-
-                    let random_gif = {
-                        if !birthday_gifs.is_empty() {
-                            let mut rng = thread_rng();
-                            &birthday_gifs[rng.gen_range(0..birthday_gifs.len())].gif_url
-                        } else {
-                            ""
-                        }
-                    };
-
-                    let embed = serenity::CreateEmbed::new()
-                        .colour(0xFF0095)
-                        .thumbnail("https://media.istockphoto.com/vectors/birthday-cake-vector-isolated-vector-id901911608?k=6&m=901911608&s=612x612&w=0&h=d6v27h_mYUaUe0iSrtoX5fTw-2wGVIY4UTbQPeI-T5k=")
-                        .title(bday_msg)
-                        .image(random_gif);
-
-                    let msg = serenity::CreateMessage::new()
-                        .content("@everyone :birthday:")
-                        .embed(embed);
-
-                    channel_id.send_message(&ctx, msg).await.unwrap();
-
-                    // Give birthday role
-                    if guild.birthday_role.is_some() {
-                        let birthday_guild = serenity::GuildId::new(guild.guild_id);
-                        let birthday_member = birthday_guild.member(&ctx, serenity::UserId::new(birthday.user_id)).await.unwrap();
-
-                        birthday_member.add_role(&ctx, serenity::RoleId::new(guild.birthday_role.unwrap())).await.unwrap();
-                    }
-                } else {
-                    // Remove birthday role if member has it
-                    if guild.birthday_role.is_some() {
-                        let birthday_guild = serenity::GuildId::new(guild.guild_id);
-                        let birthday_member = birthday_guild.member(&ctx, serenity::UserId::new(birthday.user_id)).await;
-
-                        match birthday_member {
-                            Ok(m) => m.remove_role(&ctx, serenity::RoleId::new(guild.birthday_role.unwrap())).await.unwrap(),
-                            Err(e) => println!("{:?}", e)
-                        }
-                    }
+            continue;
+        }
+
+        for birthday in birthdays_on_date {
+            let announced: Result<(), Error> = async {
+                let username = determine_username(ctx, &data.database, birthday.nickname.clone(), birthday.guild_id, birthday.user_id).await?;
+
+                let mut embed = build_announcement_embed(guild, birthday, &username, &server_name, date, belated)
+                    .image(pick_random_gif(&birthday_gifs));
+
+                // Show the birthday person's avatar if they can still be found
+                if let Ok(user) = serenity::UserId::new(birthday.user_id).to_user(ctx).await {
+                    embed = embed.thumbnail(user.face());
+                }
+
+                let mut msg = serenity::CreateMessage::new()
+                    .embed(embed);
+
+                if let Some(mention) = announcement_mention(guild, &[birthday.user_id]) {
+                    msg = msg.content(mention);
                 }
+
+                channel_id.send_message(&ctx, msg).await?;
+                data.database.add_birthday_announcements(guild.guild_id, &[birthday.user_id], date).await?;
+                Ok(())
+            }.await;
+
+            // One failed announcement shouldn't hold back the others
+            if let Err(e) = announced {
+                error!("[ BIRTHDAY ] Unable to announce a birthday - Guild ID: {} - User ID: {} - {e:?}", guild.guild_id, birthday.user_id);
+                first_error.get_or_insert(e);
             }
         }
+
+        if first_error.is_none() {
+            delivered_through = date;
+        }
+    }
+
+    data.database.set_birthday_last_announced(guild.guild_id, delivered_through).await?;
+    data.database.clear_birthday_announcements(guild.guild_id, delivered_through).await?;
+
+    // Returning the error gets the check retried, the role is handled once everything was announced
+    if let Some(e) = first_error {
+        return Err(e);
     }
-}
\ No newline at end of file
+
+    // The birthday role only follows today's birthdays and is taken away by expire_birthday_roles once the day ends
+    if due_date == today && let Some(role_id) = guild.birthday_role {
+        let expires_at = birthday_role_expiry(guild, today, current_time);
+
+        for birthday in guild_birthdays.iter().filter(|b| is_birthday_on(b, today, leap_day)) {
+            let added = ctx.http.add_member_role(
+                serenity::GuildId::new(guild.guild_id),
+                serenity::UserId::new(birthday.user_id),
+                serenity::RoleId::new(role_id),
+                Some("Birthday")
+            ).await;
+
+            match added {
+                Ok(_) => data.database.add_birthday_role_grant(guild.guild_id, birthday.user_id, role_id, expires_at).await?,
+                Err(e) => warn!("[ BIRTHDAY ] Unable to give the birthday role - Guild ID: {} - User ID: {} - {e}", guild.guild_id, birthday.user_id)
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// DM members who asked to hear about a birthday ahead of time. Sent alongside the guild's announcements, catching up on
+// days missed while the bot was down as long as the birthday is still to come.
+async fn guild_birthday_reminders(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &BirthdayGuild,
+    current_time: DateTime<Utc>
+) -> Result<(), Error> {
+    let local_time = current_time.with_timezone(&guild.tz());
+    let today = local_time.date_naive();
+
+    if local_time.hour() < guild.birthday_hour as u32 {
+        return Ok(());
+    }
+
+    let first_date = match guild.birthday_last_reminded {
+        Some(last_reminded) if last_reminded >= today => return Ok(()),
+        // Reminders are at most 30 days ahead, so anything older is about a birthday that already passed
+        Some(last_reminded) => last_reminded.succ_opt().unwrap().max(today - Duration::days(30)),
+        None => today,
+    };
+
+    let server_name = serenity::GuildId::new(guild.guild_id).name(ctx).unwrap_or_default();
+    let leap_day = guild.leap_day();
+    let guild_birthdays = data.database.list_birthdays(guild.guild_id, None).await?;
+
+    for reminder in data.database.get_guild_birthday_reminders(guild.guild_id).await? {
+        // Private birthdays are never sent out, even to people who added them before they went private
+        let Some(birthday) = guild_birthdays.iter().find(|b| b.user_id == reminder.target_user_id && !b.private) else {
+            continue;
+        };
+
+        let Some(date) = reminder_birthday_date(birthday, reminder.days, first_date, today, leap_day) else {
+            continue;
+        };
+
+        let username = determine_username(ctx, &data.database, birthday.nickname.clone(), guild.guild_id, birthday.user_id).await?;
+        let days_left = (date - today).num_days();
+        let days = if days_left == 1 { String::from("1 day") } else { format!("{days_left} days") };
+
+        let msg = serenity::CreateMessage::new()
+            .content(format!("Heads up! :birthday: {username}'s birthday is in {days}, on {} {}. (From {server_name})", MONTHS[date.month0() as usize], date.day()));
+
+        // Members with DMs closed just miss out
+        if let Err(e) = serenity::UserId::new(reminder.user_id).direct_message(ctx, msg).await {
+            warn!("[ BIRTHDAY ] Unable to DM a birthday reminder - Guild ID: {} - User ID: {} - {e}", guild.guild_id, reminder.user_id);
+        }
+    }
+
+    data.database.set_birthday_last_reminded(guild.guild_id, today).await?;
+
+    Ok(())
+}
+
+// The birthday a reminder sent `days` ahead on any day from `first_date` through `today` is about.
+// Birthdays that are today or already passed are left to the announcements.
+fn reminder_birthday_date(birthday: &Birthday, days: u8, first_date: NaiveDate, today: NaiveDate, leap_day: LeapDayPolicy) -> Option<NaiveDate> {
+    let earliest = (first_date + Duration::days(days as i64)).max(today.succ_opt().unwrap());
+
+    earliest.iter_days()
+        .take_while(|d| *d <= today + Duration::days(days as i64))
+        .find(|d| is_birthday_on(birthday, *d, leap_day))
+}
+
+// Take away every birthday role whose day has ended. Also run on startup to clean up grants that expired while the bot was down.
+pub async fn expire_birthday_roles(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let expired_grants = data.database.get_expired_birthday_role_grants(Utc::now().timestamp()).await?;
+
+    for grant in expired_grants {
+        let removed = ctx.http.remove_member_role(
+            serenity::GuildId::new(grant.guild_id),
+            serenity::UserId::new(grant.user_id),
+            serenity::RoleId::new(grant.role_id),
+            Some("Birthday is over")
+        ).await;
+
+        match removed {
+            Ok(_) => {},
+            // The member left, the role was deleted or the bot lost access. Retrying won't help so forget the grant.
+            Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(e))) if e.status_code.is_client_error() => {
+                warn!("[ BIRTHDAY ] Dropping birthday role grant that can't be removed - Guild ID: {} - User ID: {} - {}", grant.guild_id, grant.user_id, e.error.message);
+            },
+            // Anything else is kept for the next run
+            Err(e) => {
+                error!("[ BIRTHDAY ] Unable to remove the birthday role - Guild ID: {} - User ID: {} - {e}", grant.guild_id, grant.user_id);
+                continue;
+            }
+        }
+
+        data.database.delete_birthday_role_grant(&grant).await?;
+    }
+
+    Ok(())
+}
+
+// Take the birthday role away from members who were given it before grants were tracked, since nothing would ever remove it.
+// Runs on startup for guilds that had a birthday role when grants were added, until each one was swept.
+pub async fn sweep_legacy_birthday_roles(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let current_time = Utc::now();
+
+    for guild in data.database.get_birthday_role_sweep_guilds().await? {
+        if let Err(e) = sweep_guild_birthday_role(ctx, data, &guild, current_time).await {
+            error!("[ BIRTHDAY ] Unable to sweep old birthday roles, trying again on the next startup - Guild ID: {} - {e:?}", guild.guild_id);
+            continue;
+        }
+
+        data.database.finish_birthday_role_sweep(guild.guild_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn sweep_guild_birthday_role(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &BirthdayGuild,
+    current_time: DateTime<Utc>
+) -> Result<(), Error> {
+    let Some(role_id) = guild.birthday_role else {
+        return Ok(());
+    };
+    let guild_id = serenity::GuildId::new(guild.guild_id);
+    let role_id = serenity::RoleId::new(role_id);
+    let today = current_time.with_timezone(&guild.tz()).date_naive();
+    let leap_day = guild.leap_day();
+
+    // Members celebrating today keep the role until the day ends, like any other grant
+    let celebrating: HashSet<u64> = data.database.list_birthdays(guild.guild_id, None).await?
+        .iter()
+        .filter(|b| is_birthday_on(b, today, leap_day))
+        .map(|b| b.user_id)
+        .collect();
+    let expires_at = birthday_role_expiry(guild, today, current_time);
+
+    let mut after = None;
+    loop {
+        let members = guild_id.members(ctx, Some(1000), after).await?;
+
+        for member in members.iter().filter(|m| m.roles.contains(&role_id)) {
+            let user_id = member.user.id.get();
+
+            if celebrating.contains(&user_id) {
+                data.database.add_birthday_role_grant(guild.guild_id, user_id, role_id.get(), expires_at).await?;
+            } else if let Err(e) = ctx.http.remove_member_role(guild_id, member.user.id, role_id, Some("Birthday is over")).await {
+                warn!("[ BIRTHDAY ] Unable to remove an old birthday role - Guild ID: {} - User ID: {user_id} - {e}", guild.guild_id);
+            }
+        }
+
+        if members.len() < 1000 {
+            break;
+        }
+        after = members.last().map(|m| m.user.id);
+    }
+
+    Ok(())
+}
+
+// When a birthday role given today is taken away: the start of the next day in the guild's timezone
+fn birthday_role_expiry(guild: &BirthdayGuild, today: NaiveDate, current_time: DateTime<Utc>) -> i64 {
+    let tomorrow = today.succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap();
+
+    guild.tz().from_local_datetime(&tomorrow)
+        .earliest()
+        .map_or(current_time + Duration::days(1), |t| t.with_timezone(&Utc))
+        .timestamp()
+}
+
+fn is_birthday_on(birthday: &Birthday, date: NaiveDate, leap_day: LeapDayPolicy) -> bool {
+    birthday.date_in_year(date.year(), leap_day) == Some(date)
+}
+
+//--------------------
+// Tests
+//--------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn birthday(month: u8, day: u8) -> Birthday {
+        Birthday {
+            guild_id: 1,
+            user_id: 2,
+            birthday: day,
+            birthmonth: month,
+            birthyear: None,
+            nickname: None,
+            private: false,
+            show_age: false,
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn validate_birthday_rejects_impossible_dates() {
+        assert!(validate_birthday(2, 29, None).is_ok());
+        assert!(validate_birthday(12, 31, None).is_ok());
+        assert!(validate_birthday(2, 30, None).is_err());
+        assert!(validate_birthday(4, 31, None).is_err());
+    }
+
+    #[test]
+    fn validate_birthday_checks_the_year() {
+        assert!(validate_birthday(2, 29, Some(2000)).is_ok());
+        assert!(validate_birthday(2, 29, Some(2001)).is_err());
+        assert!(validate_birthday(1, 1, Some(9999)).is_err());
+    }
+
+    #[test]
+    fn leap_day_birthdays_follow_the_policy() {
+        let leapling = birthday(2, 29);
+
+        assert!(is_birthday_on(&leapling, date(2024, 2, 29), LeapDayPolicy::Feb28));
+        assert!(!is_birthday_on(&leapling, date(2024, 2, 28), LeapDayPolicy::Feb28));
+        assert!(is_birthday_on(&leapling, date(2025, 2, 28), LeapDayPolicy::Feb28));
+        assert!(!is_birthday_on(&leapling, date(2025, 3, 1), LeapDayPolicy::Feb28));
+        assert!(is_birthday_on(&leapling, date(2025, 3, 1), LeapDayPolicy::Mar1));
+        assert!(!is_birthday_on(&leapling, date(2025, 2, 28), LeapDayPolicy::Mar1));
+    }
+
+    #[test]
+    fn date_in_year_moves_leap_day_birthdays() {
+        let leapling = birthday(2, 29);
+
+        assert_eq!(leapling.date_in_year(2024, LeapDayPolicy::Mar1), Some(date(2024, 2, 29)));
+        assert_eq!(leapling.date_in_year(2025, LeapDayPolicy::Feb28), Some(date(2025, 2, 28)));
+        assert_eq!(leapling.date_in_year(2025, LeapDayPolicy::Mar1), Some(date(2025, 3, 1)));
+        assert_eq!(birthday(7, 4).date_in_year(2025, LeapDayPolicy::Mar1), Some(date(2025, 7, 4)));
+    }
+
+    #[test]
+    fn next_date_counts_today_and_rolls_over() {
+        let summer = birthday(7, 4);
+        assert_eq!(summer.next_date(date(2025, 7, 4), LeapDayPolicy::Feb28), Some(date(2025, 7, 4)));
+        assert_eq!(summer.next_date(date(2025, 7, 5), LeapDayPolicy::Feb28), Some(date(2026, 7, 4)));
+
+        let leapling = birthday(2, 29);
+        assert_eq!(leapling.next_date(date(2025, 3, 2), LeapDayPolicy::Mar1), Some(date(2026, 3, 1)));
+        assert_eq!(leapling.next_date(date(2027, 3, 1), LeapDayPolicy::Feb28), Some(date(2028, 2, 29)));
+    }
+
+    #[test]
+    fn parse_csv_line_handles_quoted_fields() {
+        assert_eq!(parse_csv_line("1, 2 ,3"), vec!["1", "2", "3"]);
+        assert_eq!(parse_csv_line(r#"1,2,3,"Smith, Jr.""#), vec!["1", "2", "3", "Smith, Jr."]);
+        assert_eq!(parse_csv_line(r#"1,2,3,"The ""Great"" One""#), vec!["1", "2", "3", "The \"Great\" One"]);
+        assert_eq!(parse_csv_line("1,2,3,"), vec!["1", "2", "3", ""]);
+    }
+
+    #[test]
+    fn csv_field_survives_a_round_trip() {
+        assert_eq!(csv_field("Sam"), "Sam");
+        assert_eq!(parse_csv_line(&csv_field("Smith, \"Jr.\"")), vec!["Smith, \"Jr.\""]);
+    }
+
+    #[test]
+    fn header_rows_are_detected() {
+        assert!(is_header_row(&parse_csv_line("user_id,month,day,nickname")));
+        assert!(!is_header_row(&parse_csv_line("123,1,1,Sam")));
+    }
+
+    #[test]
+    fn parse_import_row_validates_each_column() {
+        let row = |line: &str| parse_import_row(1, &parse_csv_line(line));
+
+        let imported = row("123,2,29,Leapling").unwrap();
+        assert_eq!((imported.user_id, imported.birthmonth, imported.birthday), (123, 2, 29));
+        assert_eq!(imported.nickname.as_deref(), Some("Leapling"));
+        assert_eq!(row("123,12,25,").unwrap().nickname, None);
+        assert_eq!(row("123,12,25").unwrap().nickname, None);
+
+        assert!(row("abc,1,1").is_err());
+        assert!(row("123,13,1").is_err());
+        assert!(row("123,2,30").is_err());
+        assert!(row("123,1").is_err());
+        assert!(row("123,1,1,Sam,extra").is_err());
+    }
+
+    #[test]
+    fn calendar_escapes_names_and_repeats_yearly() {
+        let birthdays = vec![(birthday(2, 29), String::from("Smith, Jr.; the 2nd"))];
+        let calendar = build_birthday_calendar("Test", &birthdays, LeapDayPolicy::Mar1);
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nDTSTART;VALUE=DATE:20000229\r\n"));
+        assert!(calendar.contains("\r\nRRULE:FREQ=YEARLY;BYYEARDAY=60\r\n"));
+        assert!(calendar.contains("\r\nSUMMARY:Smith\\, Jr.\\; the 2nd's Birthday\r\n"));
+    }
+
+    #[test]
+    fn calendar_lines_are_folded_and_never_hold_a_raw_carriage_return() {
+        let calendar = build_birthday_calendar(&"é".repeat(60), &[], LeapDayPolicy::Feb28);
+
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+        assert!(calendar.contains("\r\n é"));
+        assert!(calendar.replace("\r\n ", "").contains(&format!("\r\nX-WR-CALNAME:{}\r\n", "é".repeat(60))));
+        assert_eq!(calendar.matches('\r').count(), calendar.matches("\r\n").count());
+        assert_eq!(ics_text("a\r\nb\rc"), "a\\nb\\nc");
+    }
+
+    #[test]
+    fn unknown_placeholders_lists_unsupported_ones() {
+        assert!(unknown_placeholders("Happy birthday {name} from {server}!").is_empty());
+        assert_eq!(unknown_placeholders("Hi {nmae}, {age} {user}"), vec!["{nmae}", "{user}"]);
+    }
+
+    #[test]
+    fn render_birthday_message_fills_in_placeholders_once() {
+        let rendered = render_birthday_message("{mention} {name} turns {age} in {server} {other}", "{server}", 42, Some(30), "Guild");
+
+        assert_eq!(rendered, "<@42> {server} turns 30 in Guild {other}");
+        assert_eq!(render_birthday_message("Turning {age}!", "Sam", 1, None, "Guild"), "Turning !");
+    }
+
+    #[test]
+    fn age_sections_are_left_out_without_an_age() {
+        let template = "Happy birthday {name}![ You're {age} now!] [Enjoy]";
+
+        assert_eq!(render_birthday_message(template, "Sam", 1, Some(21), "Guild"), "Happy birthday Sam! You're 21 now! [Enjoy]");
+        assert_eq!(render_birthday_message(template, "Sam", 1, None, "Guild"), "Happy birthday Sam! [Enjoy]");
+    }
+
+    #[test]
+    fn bare_age_is_only_found_outside_sections() {
+        assert!(has_bare_age("Happy {age}th birthday!"));
+        assert!(has_bare_age("[Hi] {age}"));
+        assert!(!has_bare_age("Happy birthday![ You're {age}!]"));
+    }
+
+    #[test]
+    fn blank_nicknames_are_not_stored() {
+        assert_eq!(clean_nickname(None), None);
+        assert_eq!(clean_nickname(Some(String::new())), None);
+        assert_eq!(clean_nickname(Some("   ".into())), None);
+        assert_eq!(clean_nickname(Some("Sam".into())), Some("Sam".into()));
+    }
+
+    #[test]
+    fn reminders_catch_up_only_on_birthdays_still_to_come() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        let today = date(3, 5);
+
+        assert_eq!(reminder_birthday_date(&birthday(3, 10), 5, today, today, LeapDayPolicy::Feb28), Some(date(3, 10)));
+        assert_eq!(reminder_birthday_date(&birthday(3, 10), 3, today, today, LeapDayPolicy::Feb28), None);
+
+        // Missed since March 1st, the reminder for the 10th is still worth sending
+        assert_eq!(reminder_birthday_date(&birthday(3, 10), 8, date(3, 1), today, LeapDayPolicy::Feb28), Some(date(3, 10)));
+        assert_eq!(reminder_birthday_date(&birthday(3, 4), 2, date(3, 1), today, LeapDayPolicy::Feb28), None);
+        assert_eq!(reminder_birthday_date(&birthday(3, 5), 4, date(3, 1), today, LeapDayPolicy::Feb28), None);
+    }
+}
@@ -25,20 +25,14 @@ pub async fn setmessage(
 
     // Handle empty channel (disables messaging)
     if message.is_none() {
-        sqlx::query!("UPDATE boost SET message = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_boost_message(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer sending a custom boost message!", ctx.author())).await?;
         return Ok(());
     }
 
     // Grab channel ID and query DB
-    sqlx::query!("UPDATE boost SET message = ? WHERE guild_id = ?", message.as_ref().unwrap(), guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_boost_message(guild_id, message.as_deref()).await?;
 
     ctx.say(format!("{}, set the custom boost message to: ```{}```", ctx.author(), message.unwrap())).await?;
     Ok(())
@@ -57,20 +51,14 @@ pub async fn setimage(
 
     // Handle empty channel (disables messaging)
     if image_url.is_none() {
-        sqlx::query!("UPDATE boost SET image_url = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_boost_image(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer including an image in the boost messages!", ctx.author())).await?;
         return Ok(());
     }
 
     // Query DB
-    sqlx::query!("UPDATE boost SET image_url = ? WHERE guild_id = ?", image_url.as_ref().unwrap(), guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_boost_image(guild_id, image_url.as_deref()).await?;
 
     ctx.say(format!("{}, image for the boost messages set to {}", ctx.author(), image_url.unwrap())).await?;
     Ok(())
@@ -86,10 +74,7 @@ pub async fn setchannel(
 
     // Handle empty channel (disables messaging)
     if channel.is_none() {
-        sqlx::query!("UPDATE boost SET channel_id = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_boost_channel(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer sending boost messages!", ctx.author())).await?;
         return Ok(());
@@ -98,10 +83,7 @@ pub async fn setchannel(
     // Grab channel ID and query DB
     let channel_id = channel.as_ref().unwrap().id().get();
 
-    sqlx::query!("UPDATE boost SET channel_id = ? WHERE guild_id = ?", channel_id, guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_boost_channel(guild_id, Some(channel_id)).await?;
 
     ctx.say(format!("{}, now sending boost messages to {}!", ctx.author(), channel.unwrap())).await?;
     Ok(())
@@ -114,12 +96,9 @@ pub async fn setchannel(
 async fn boost_channel_check(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    let channel = sqlx::query!("SELECT channel_id FROM boost WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let settings = ctx.data().database.get_boost_settings(guild_id).await?;
 
-    match channel.channel_id {
+    match settings.channel_id {
         Some(_) => Ok(true),
         None => {
             ctx.send(poise::CreateReply::default()
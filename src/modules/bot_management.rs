@@ -1,4 +1,9 @@
 use crate::{Context, Error};
+use crate::scheduler;
+use crate::storage::migrations::MigrationState;
+use chrono::Utc;
+use poise::serenity_prelude as serenity;
+use tracing::info;
 
 // Bot Gif Structure
 #[derive(poise::ChoiceParameter)]
@@ -29,14 +34,121 @@ pub async fn set_bot_gif(
     #[description = "The type of bot GIF to set."] bot_gif: BotGif,
     #[description = "GIF Url"] url: String,
 ) -> Result<(), Error> {
-   let query = format!("UPDATE bot_settings SET {bot_gif}_gif = {url}");
-
-   sqlx::raw_sql(&query)
-       .execute(&ctx.data().database)
-       .await
-       .unwrap();
+    ctx.data().database.set_bot_gif(&format!("{bot_gif}_gif"), &url).await?;
 
     ctx.say(format!("Updated {bot_gif}_gif with {url}")).await?;
 
     Ok(())
 }
+
+/// Show which database migrations have been applied
+#[poise::command(
+    slash_command,
+    owners_only,
+    hide_in_help,
+    ephemeral
+)]
+pub async fn migration_status(ctx: Context<'_>) -> Result<(), Error> {
+    let status = ctx.data().database.migration_status().await?;
+
+    let applied = status.iter().filter(|m| m.state == MigrationState::Applied).count();
+    let latest = status.iter().rfind(|m| m.state == MigrationState::Applied);
+
+    let mut embed_desc = format!("**Backend:** {}\n**Applied:** {applied}/{}\n", ctx.data().database.backend_name(), status.len());
+    if let Some(m) = latest {
+        embed_desc = format!("{embed_desc}**Latest:** `{}` {}\n", m.version, m.description);
+    }
+
+    // Only list the migrations that need attention
+    let outstanding: Vec<String> = status.iter()
+        .filter(|m| m.state != MigrationState::Applied)
+        .map(|m| format!("`{}` {} - **{:?}**", m.version, m.description, m.state))
+        .collect();
+
+    if outstanding.is_empty() {
+        embed_desc = format!("{embed_desc}\nThe schema is up to date!");
+    } else {
+        embed_desc = format!("{embed_desc}\n{}", outstanding.join("\n"));
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Database Migrations")
+        .description(embed_desc)
+        .colour(if outstanding.is_empty() { 0x00FF00 } else { 0xFF0000 });
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// List and run scheduled jobs
+#[poise::command(
+    slash_command,
+    owners_only,
+    hide_in_help,
+    subcommands("list", "run"),
+)]
+pub async fn jobs(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List every scheduled job with its last and next run
+#[poise::command(slash_command, ephemeral)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let mut embed_desc = String::new();
+
+    for job in scheduler::JOBS {
+        let last_run = match ctx.data().database.get_job_last_run(job.name).await? {
+            Some(timestamp) => format!("<t:{timestamp}:R>"),
+            None => String::from("Never"),
+        };
+        let next_run = match job.cron().upcoming(Utc).next() {
+            Some(time) => format!("<t:{}:R>", time.timestamp()),
+            None => String::from("Never"),
+        };
+
+        embed_desc = format!("{embed_desc}**{}** - `{}`\nLast run: {last_run} - Next run: {next_run}\n\n", job.name, job.schedule);
+    }
+
+    let embed = serenity::CreateEmbed::new()
+        .title("Scheduled Jobs")
+        .description(embed_desc)
+        .colour(0x00FF00);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Run a scheduled job right now
+#[poise::command(slash_command, ephemeral)]
+pub async fn run(
+    ctx: Context<'_>,
+    #[description = "The job to run."]
+    #[autocomplete = "autocomplete_job"]
+    job: String,
+) -> Result<(), Error> {
+    let Some(job) = scheduler::find_job(&job) else {
+        return Err(format!("There is no job named `{job}`!").into());
+    };
+
+    ctx.defer_ephemeral().await?;
+    job.trigger(ctx.serenity_context(), ctx.data(), Utc::now()).await?;
+
+    info!("[ SCHEDULER ] Job {} was run manually by User ID: {}", job.name, ctx.author().id.get());
+    ctx.say(format!("Finished running `{}`!", job.name)).await?;
+
+    Ok(())
+}
+
+//--------------------
+// Function library
+//--------------------
+async fn autocomplete_job<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = &'static str> + 'a {
+    scheduler::JOBS.iter()
+        .map(|job| job.name)
+        .filter(move |name| name.contains(partial))
+}
@@ -1,4 +1,5 @@
 use crate::{Context, Error};
+use crate::storage::Storage;
 use poise::serenity_prelude as serenity;
 
 //--------------------
@@ -37,6 +38,7 @@ impl std::fmt::Display for GIFType {
     }
 }
 
+#[derive(sqlx::FromRow)]
 #[allow(dead_code)]
 pub struct CustomGif {
     pub guild_id: u64,
@@ -75,49 +77,37 @@ fn create_gif_pages(gifs: Vec<CustomGif>) -> Vec<serenity::CreateEmbed> {
 
 // Grab specific type of GIFs for the server
 pub async fn grab_custom_gifs(
-    database: &sqlx::MySqlPool,
+    database: &Storage,
     gif_type: &GIFType,
     guild_id: u64,
     query_type: GIFDBQueryType
-) -> Vec<CustomGif> {
-    // Keep using query_as!() and .fetch_all() to keep the Vector type and not have to deal with whether it's a single item or not in this function.
-    match query_type {
-        GIFDBQueryType::Normal => { 
-            sqlx::query_as!(CustomGif, "SELECT * FROM custom_gifs WHERE guild_id = ? AND gif_type = ?", guild_id, gif_type.to_string())
-                .fetch_all(database)
-                .await
-                .unwrap()
-        },
-        GIFDBQueryType::SingleRandom => {
-            sqlx::query_as!(CustomGif, "SELECT * FROM custom_gifs WHERE guild_id = ? AND gif_type = ? ORDER BY RAND() LIMIT 1", guild_id, gif_type.to_string())
-                .fetch_all(database)
-                .await
-                .unwrap()
-        }
-    }
+) -> Result<Vec<CustomGif>, Error> {
+    // Keep returning a Vector to not have to deal with whether it's a single item or not in this function.
+    let gifs = match query_type {
+        GIFDBQueryType::Normal => database.get_custom_gifs(guild_id, &gif_type.to_string()).await?,
+        GIFDBQueryType::SingleRandom => database.get_random_custom_gif(guild_id, &gif_type.to_string()).await?.into_iter().collect()
+    };
+
+    Ok(gifs)
 }
 
 // Check if GIF commands require roles
 async fn check_gif_role(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().unwrap().get();
-    let role_id = sqlx::query!("SELECT custom_gifs_required_role FROM guild_settings WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .custom_gifs_required_role.unwrap_or(0);
+    let role_id = ctx.data().database.get_gifs_required_role(guild_id).await?.unwrap_or(0);
 
     if role_id == 0 {
         return Ok(true);
     }
 
     let role_id = serenity::RoleId::new(role_id);
-    let guild_roles = ctx.guild_id().unwrap().roles(&ctx.http()).await.unwrap();
+    let guild_roles = ctx.guild_id().unwrap().roles(&ctx.http()).await?;
     let role = match guild_roles.get(&role_id) {
         Some(r) => r,
-        None => return Err("A role is set to be required for this command, but it doesn't exist!".into())
+        None => return Err(Error::Permission("A role is set to be required for this command, but it doesn't exist!".to_string()))
     };
 
-    if !ctx.author().has_role(ctx.http(), guild_id, role_id).await.unwrap() {
+    if !ctx.author().has_role(ctx.http(), guild_id, role_id).await? {
         ctx.send(
     poise::CreateReply::default()
                 .content(format!("You must have the '{}' role to run this command!", role.name))
@@ -132,17 +122,17 @@ async fn check_gif_role(ctx: Context<'_>) -> Result<bool, Error> {
 
 // Grab random GIF attachment.
 pub async fn grab_misc_gif(
-    database: &sqlx::MySqlPool,
+    database: &Storage,
     guild_id: u64,
     gif_type: &GIFType
-) -> Option<String> {
-    let random_gif = grab_custom_gifs(database, gif_type, guild_id, GIFDBQueryType::SingleRandom).await;
+) -> Result<Option<String>, Error> {
+    let random_gif = grab_custom_gifs(database, gif_type, guild_id, GIFDBQueryType::SingleRandom).await?;
     
     if !random_gif.is_empty() {
         let url = random_gif[0].gif_url.to_owned();
-        Some(url)
+        Ok(Some(url))
     } else {
-        None
+        Ok(None)
     }
 }
 
@@ -171,19 +161,8 @@ pub async fn addgif(
     let guild_id = ctx.guild_id().unwrap().get();
     let gif_type = command.to_string();
 
-    // Set future GIF ID
-    let gif_id = sqlx::query!("SELECT MAX(gif_id) AS gif_id FROM custom_gifs WHERE guild_id = ? AND gif_type = ?", guild_id, gif_type)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .gif_id
-        .unwrap_or(0);
-    
     // Insert into DB
-    sqlx::query!("INSERT INTO custom_gifs (guild_id, gif_type, gif_id, gif_url, gif_name) VALUES (?, ?, ?, ?, ?)", guild_id, gif_type, gif_id + 1, gif_url, gif_name)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.add_custom_gif(guild_id, &gif_type, &gif_name, &gif_url).await?;
 
     ctx.say(format!("Registered a new GIF for \"{gif_type}\"! {gif_url}")).await?;
 
@@ -208,28 +187,13 @@ pub async fn delgif(
     let guild_id = ctx.guild_id().unwrap().get();
     let gif_type = command.to_string();
 
-    // Determine if GIF ID exists
-    let count = sqlx::query!("SELECT COUNT(gif_id) AS count FROM custom_gifs WHERE guild_id = ? AND gif_type = ? AND gif_id = ?", guild_id, gif_type, gif_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .count;
+    // Remove GIF from database and then reset IDs
+    let deleted = ctx.data().database.delete_custom_gif(guild_id, &gif_type, gif_id).await?;
 
-    if count < 1 {
+    if !deleted {
         return Err(format!("No GIF has been registered with that ID under \"{gif_type}\"!").into());
     }
 
-    // Remove GIF from database and then reset IDs
-    sqlx::query!("DELETE FROM custom_gifs WHERE guild_id = ? AND gif_type = ? AND gif_id = ?", guild_id, gif_type, gif_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
-
-    sqlx::query!("UPDATE custom_gifs SET gif_id = gif_id - 1 WHERE guild_id = ? AND gif_type = ? AND gif_id > ?", guild_id, gif_type, gif_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
-
     ctx.say(format!("Deleted GIF from \"{gif_type}\"!")).await?;
 
     Ok(())
@@ -248,7 +212,7 @@ pub async fn listgifs(
     gif_type: GIFType
 ) -> Result<(), Error> {
     // Grab relevant GIFs, return error if empty
-    let gifs = grab_custom_gifs(&ctx.data().database, &gif_type, ctx.guild_id().unwrap().get(), GIFDBQueryType::Normal).await;
+    let gifs = grab_custom_gifs(&ctx.data().database, &gif_type, ctx.guild_id().unwrap().get(), GIFDBQueryType::Normal).await?;
     
     if gifs.is_empty() {
         return Err(format!("No GIFs were found for \"{gif_type}\"").into());
@@ -321,10 +285,7 @@ pub async fn setgifrole(
 
     // Remove the role if none is specified
     if gif_role.is_none() {
-        sqlx::query!("UPDATE guild_settings SET custom_gifs_required_role = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_gifs_required_role(guild_id, None).await?;
 
         ctx.say("No longer requiring a role to modify custom GIFs!").await?;
         return Ok(());
@@ -333,10 +294,7 @@ pub async fn setgifrole(
     // Add role otherwise
     let role_id = gif_role.as_ref().unwrap().id.get();
 
-    sqlx::query!("UPDATE guild_settings SET custom_gifs_required_role = ? WHERE guild_id = ?", role_id, guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_gifs_required_role(guild_id, Some(role_id)).await?;
 
     ctx.say(format!("Now requiring the {} role to use custom GIF commands.", gif_role.unwrap().name)).await?;
 
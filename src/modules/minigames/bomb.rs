@@ -88,11 +88,8 @@ pub async fn bomb(
 
     // Handle stats and check for target
     if !targeting_self {
-        user_table_check(ctx, &target).await;
-        sqlx::query!("UPDATE users SET bomb_sent = bomb_sent + 1 WHERE guild_id = ? AND user_id = ?", bomb.guild_id, bomb.sender)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        user_table_check(ctx, &target).await?;
+        ctx.data().database.increment_user_stat(bomb.guild_id, bomb.sender, "bomb_sent").await?;
     }
 
     // Button clicking event
@@ -121,7 +118,7 @@ pub async fn bomb(
         if press.data.custom_id == bomb.wire_id {
             // Handle interaction
             let mut msg = press.message.clone();
-            let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombDefuse).await.unwrap_or(String::new());
+            let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombDefuse).await?.unwrap_or(String::new());
             msg.edit(ctx, 
                 serenity::EditMessage::new()
                     .embed(serenity::CreateEmbed::new()
@@ -135,10 +132,7 @@ pub async fn bomb(
 
             // Handle stats for target
             if !targeting_self {
-                sqlx::query!("UPDATE users SET bomb_defused = bomb_defused + 1 WHERE guild_id = ? AND user_id = ?", bomb.guild_id, bomb.target)
-                    .execute(&ctx.data().database)
-                    .await
-                    .unwrap();
+                ctx.data().database.increment_user_stat(bomb.guild_id, bomb.target, "bomb_defused").await?;
             }
 
             break;
@@ -148,7 +142,7 @@ pub async fn bomb(
         if press.data.custom_id != bomb.wire_id  {
             // Handle interaction
             let mut msg = press.message.clone();
-            let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombFailure).await.unwrap_or(String::new());
+            let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombFailure).await?.unwrap_or(String::new());
             msg.edit(ctx, 
                 serenity::EditMessage::new()
                     .embed(serenity::CreateEmbed::new()
@@ -162,10 +156,7 @@ pub async fn bomb(
 
             // Handle target stats
             if !targeting_self {
-                sqlx::query!("UPDATE users SET bomb_failed = bomb_failed + 1 WHERE guild_id = ? AND user_id = ?", bomb.guild_id, bomb.target)
-                    .execute(&ctx.data().database)
-                    .await
-                    .unwrap();
+                ctx.data().database.increment_user_stat(bomb.guild_id, bomb.target, "bomb_failed").await?;
             }
 
             break;
@@ -174,7 +165,7 @@ pub async fn bomb(
 
     // Check if bomb is still active after timeout
     if !bomb.exploded {
-        let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombTime).await.unwrap_or(String::new());
+        let gif = grab_misc_gif(&ctx.data().database, bomb.guild_id, &GIFType::BombTime).await?.unwrap_or(String::new());
         msg.edit(ctx, poise::CreateReply::default()
             .embed(serenity::CreateEmbed::new()
                 .description("***KABOOM*** You ran out of time!")
@@ -185,10 +176,7 @@ pub async fn bomb(
         ).await?;
 
         if !targeting_self {
-            sqlx::query!("UPDATE users SET bomb_failed = bomb_failed + 1 WHERE guild_id = ? AND user_id = ?", bomb.guild_id, bomb.target)
-                .execute(&ctx.data().database)
-                .await
-                .unwrap();
+            ctx.data().database.increment_user_stat(bomb.guild_id, bomb.target, "bomb_failed").await?;
         }
     }
 
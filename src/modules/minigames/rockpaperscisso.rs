@@ -1,4 +1,5 @@
 use crate::{Context, Error};
+use crate::storage::Storage;
 use poise::serenity_prelude as serenity;
 
 // Choices
@@ -60,48 +61,27 @@ fn determine_winner_result(
         match victim_choice {
             RPSChoices::Paper => 1,
             RPSChoices::Rock => 2,
-            _ => return 3
+            _ => 3
         }
     }
 }
 
 async fn update_stats(
-    db: &sqlx::MySqlPool,
+    db: &Storage,
     guild_id: &u64,
     players: &[RPSPlayer; 2],
     winner: &u32
-) {
-    let query = match winner {
-        1 => {
-            format!(
-                "UPDATE users SET rps_win = rps_win + 1 WHERE guild_id = {guild_id} AND user_id = {winner_id};
-                UPDATE users SET rps_loss = rps_loss + 1 WHERE guild_id = {guild_id} AND user_id = {loser_id}",
-                winner_id = players[0].id,
-                loser_id = players[1].id
-            )
-        },
-        2 => {
-            format!(
-                "UPDATE users SET rps_win = rps_win + 1 WHERE guild_id = {guild_id} AND user_id = {winner_id};
-                UPDATE users SET rps_loss = rps_loss + 1 WHERE guild_id = {guild_id} AND user_id = {loser_id}",
-                winner_id = players[1].id,
-                loser_id = players[0].id
-            )
-        },
-        _ => {
-            format!(
-                "UPDATE users SET rps_tie = rps_tie + 1 WHERE guild_id = {guild_id} AND user_id = {player_one};
-                UPDATE users SET rps_tie = rps_tie + 1 WHERE guild_id = {guild_id} AND user_id = {player_two}",
-                player_one = players[0].id,
-                player_two = players[1].id
-            )
-        }
+) -> Result<(), Error> {
+    let (first_stat, second_stat) = match winner {
+        1 => ("rps_win", "rps_loss"),
+        2 => ("rps_loss", "rps_win"),
+        _ => ("rps_tie", "rps_tie")
     };
     
-    sqlx::raw_sql(&query)
-        .execute(db)
-        .await
-        .unwrap();
+    db.increment_user_stat(*guild_id, players[0].id, first_stat).await?;
+    db.increment_user_stat(*guild_id, players[1].id, second_stat).await?;
+
+    Ok(())
 }
 
 /// Challenge someone to rock, paper, scissors!
@@ -197,7 +177,7 @@ pub async fn rps(
         if rps_game[0].choice != RPSChoices::None && rps_game[1].choice != RPSChoices::None {
             // Determine winner to update
             let winner = determine_winner_result(&rps_game[0].choice, &rps_game[1].choice);
-            update_stats(&ctx.data().database, &guild_id, &rps_game, &winner).await;
+            update_stats(&ctx.data().database, &guild_id, &rps_game, &winner).await?;
             
             let embed_desc = match winner {
                 1 => {
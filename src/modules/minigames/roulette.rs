@@ -18,10 +18,8 @@ pub async fn roulette(ctx: Context<'_>) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
     
     // Grab game data
-    let mut roulette = sqlx::query_as!(Roulette, "SELECT roulette_chamber, roulette_count FROM guild_settings WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let (roulette_chamber, roulette_count) = ctx.data().database.get_roulette(guild_id).await?;
+    let mut roulette = Roulette { roulette_chamber, roulette_count };
     
     // If game is new, set it up
     if roulette.roulette_chamber == 0 {
@@ -35,11 +33,7 @@ pub async fn roulette(ctx: Context<'_>) -> Result<(), Error> {
     roulette.roulette_count += 1;
     
     if roulette.roulette_count == roulette.roulette_chamber {
-        let gif_url = sqlx::query!("SELECT roulette_fire_gif FROM bot_settings")
-            .fetch_one(&ctx.data().database)
-            .await
-            .unwrap()
-            .roulette_fire_gif;
+        let gif_url = ctx.data().database.get_bot_settings().await?.roulette_fire_gif;
         let msg = String::from("You pick it up and...BANG!");
         
         let embed = serenity::CreateEmbed::new()
@@ -50,18 +44,9 @@ pub async fn roulette(ctx: Context<'_>) -> Result<(), Error> {
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         
         let user_id = ctx.author().id.get();
-        let roulette_win_query = format!("UPDATE guild_settings SET roulette_chamber = 0, roulette_count = 0 WHERE guild_id = {guild_id};
-            UPDATE users SET roulette_deaths = roulette_deaths + 1 WHERE guild_id = {guild_id} AND user_id = {user_id}");
-        
-        sqlx::raw_sql(&roulette_win_query)
-            .execute(&ctx.data().database)
-            .await?;
+        ctx.data().database.record_roulette_death(guild_id, user_id).await?;
     } else {
-        let gif_url = sqlx::query!("SELECT roulette_click_gif FROM bot_settings")
-            .fetch_one(&ctx.data().database)
-            .await
-            .unwrap()
-            .roulette_click_gif;
+        let gif_url = ctx.data().database.get_bot_settings().await?.roulette_click_gif;
         let msg = format!("{}, you hear a click and nothing happens! You have survived the attempt.", ctx.author());
         
         let embed = serenity::CreateEmbed::new()
@@ -71,10 +56,7 @@ pub async fn roulette(ctx: Context<'_>) -> Result<(), Error> {
             
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         
-        sqlx::query!("UPDATE guild_settings SET roulette_chamber = ?, roulette_count = ? WHERE guild_id = ?", roulette.roulette_chamber, roulette.roulette_count, guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_roulette(guild_id, roulette.roulette_chamber, roulette.roulette_count).await?;
     }
     
     Ok(())
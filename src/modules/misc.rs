@@ -1,4 +1,5 @@
 use crate::{Context, Error};
+use crate::storage::Storage;
 use crate::data::user_table_check;
 use crate::customgifs::{grab_misc_gif, GIFType};
 use poise::serenity_prelude as serenity;
@@ -51,17 +52,13 @@ impl MiscCommand {
 
 // Determine if user is accepting pings
 async fn is_user_pingable(
-    database: &sqlx::MySqlPool,
+    database: &Storage,
     guild_id: u64,
     user_id: u64
-) -> bool {
-    let ping = sqlx::query!("SELECT command_ping FROM user_settings WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-        .fetch_one(database)
-        .await
-        .unwrap()
-        .command_ping;
+) -> Result<bool, Error> {
+    let ping = database.get_command_ping(guild_id, user_id).await?;
         
-    ping == 1
+    Ok(ping)
 }
 
 async fn handle_user_stats(
@@ -69,23 +66,22 @@ async fn handle_user_stats(
     ctx: Context<'_>,
     victim: serenity::User,
     glados_trigger: bool
-) {
+) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
     let executioner_id = ctx.author().id.get();
     let victim_id = victim.id.get();
         
-    let query = if glados_trigger {
-        format!("UPDATE users SET cake_sent = cake_sent + 1 WHERE guild_id = {guild_id} AND user_id = {executioner_id};
-            UPDATE users SET cake_glados = cake_glados + 1 WHERE guild_id = {guild_id} AND user_id = {victim_id}")
+    let (sent_stat, received_stat) = if glados_trigger {
+        (String::from("cake_sent"), String::from("cake_glados"))
     } else {
-        format!("UPDATE users SET {command}_sent = {command}_sent + 1 WHERE guild_id = {guild_id} AND user_id = {executioner_id};
-            UPDATE users SET {command}_received = {command}_received + 1 WHERE guild_id = {guild_id} AND user_id = {victim_id}")
+        (format!("{command}_sent"), format!("{command}_received"))
     };
         
-    sqlx::raw_sql(&query)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    let database = &ctx.data().database;
+    database.increment_user_stat(guild_id, executioner_id, &sent_stat).await?;
+    database.increment_user_stat(guild_id, victim_id, &received_stat).await?;
+
+    Ok(())
 }
 
 /// Slap slap slap, clap clap clap
@@ -169,7 +165,7 @@ async fn misc_container(
     // Determine GIF
     let gif_type = command.determine_gif_type(ctx.author() == &victim);
     
-    let mut random_gif = grab_misc_gif(&ctx.data().database, guild_id, &gif_type).await;
+    let mut random_gif = grab_misc_gif(&ctx.data().database, guild_id, &gif_type).await?;
     
     // Determine embed message
     let mut glados_trigger= false;
@@ -209,11 +205,7 @@ async fn misc_container(
     // Update gif for glados
     // Can't run thread_rng() with this, so I had to split it. Will work on combining this later.
     if glados_trigger {
-        let gif_url = sqlx::query!("SELECT glados_gif FROM bot_settings")
-            .fetch_one(&ctx.data().database)
-            .await
-            .unwrap()
-            .glados_gif;
+        let gif_url = ctx.data().database.get_bot_settings().await?.glados_gif;
             
         random_gif = Some(gif_url);
     }
@@ -223,7 +215,7 @@ async fn misc_container(
     let mut reply = poise::CreateReply::default();
     
     // - Ping user if allowed
-    if is_user_pingable(&ctx.data().database, guild_id, victim.id.get()).await {
+    if is_user_pingable(&ctx.data().database, guild_id, victim.id.get()).await? {
         reply = reply.content(format!("{victim}"));
     } else {
         msg = format!("{victim}: {msg}");
@@ -240,8 +232,8 @@ async fn misc_container(
     
     // Handle stats
     if ctx.author() != &victim {
-        user_table_check(ctx, &victim).await;
-        handle_user_stats(&command, ctx, victim, glados_trigger).await;
+        user_table_check(ctx, &victim).await?;
+        handle_user_stats(&command, ctx, victim, glados_trigger).await?;
     }
     
     Ok(())
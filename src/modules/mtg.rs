@@ -104,10 +104,9 @@ fn create_double_face_embed(scryfall: ScryfallMTGCard) -> Vec<serenity::CreateEm
         card_embed = add_fields(&face.power, &face.toughness, &face.loyalty, card_embed);
     
         if scryfall.image_status.as_str() != "missing" {
-            let image_url = if scryfall.image_uris.is_none() {
-                face.image_uris.as_ref().unwrap()["border_crop"].as_str().unwrap_or("")
-            } else {
-                scryfall.image_uris.as_ref().unwrap()["border_crop"].as_str().unwrap_or("")
+            let image_url = match &scryfall.image_uris {
+                Some(image_uris) => image_uris["border_crop"].as_str().unwrap_or(""),
+                None => face.image_uris.as_ref().unwrap()["border_crop"].as_str().unwrap_or("")
             };
 
             card_embed = card_embed.image(image_url);
@@ -266,17 +265,23 @@ async fn scryfall_query(
         .await;
 
     let scryfall = match scryfall {
-        Ok(data) => data.json::<serde_json::Value>().await.unwrap(),
+        Ok(data) => data.json::<serde_json::Value>().await,
+        Err(err) => Err(err)
+    };
+
+    let scryfall = match scryfall {
+        Ok(data) => data,
         Err(err) => {
             error!("[ MTG ] An error occurred trying to parse the Scryfall data: {err}");
-            return Err("There was an error processing your request!".into());
+            return Err(Error::Scryfall("There was an error processing your request!".to_string()));
         }
     };
 
     // Handle errors
-    if scryfall["object"].as_str().unwrap() == "error" {
-        error!("[ MTG ] An error occurred with data from Scryfall: {}", scryfall["details"].as_str().unwrap());
-        return Err(scryfall["details"].as_str().unwrap().into());
+    if scryfall["object"].as_str() == Some("error") {
+        let details = scryfall["details"].as_str().unwrap_or("Scryfall was unable to find that card!");
+        error!("[ MTG ] An error occurred with data from Scryfall: {details}");
+        return Err(Error::Scryfall(details.to_string()));
     }
 
     Ok(scryfall)
@@ -310,10 +315,9 @@ pub async fn card(
     let set = set.unwrap_or_default().to_lowercase();
 
     // Determine API URL and perform query
-    let api_url = if collector_num.is_some() {
-        format!("https://api.scryfall.com/cards/{}/{}", set, collector_num.unwrap())
-    } else {
-        format!("https://api.scryfall.com/cards/named?fuzzy={}&set={}", name.unwrap(), set)
+    let api_url = match collector_num {
+        Some(collector_num) => format!("https://api.scryfall.com/cards/{}/{}", set, collector_num),
+        None => format!("https://api.scryfall.com/cards/named?fuzzy={}&set={}", name.unwrap(), set)
     };
 
     let scryfall = match scryfall_query(&ctx.data().client, api_url).await {
@@ -322,7 +326,13 @@ pub async fn card(
     };
 
     // Create Embeds
-    let scryfall: ScryfallMTGCard = serde_json::from_value(scryfall).unwrap();
+    let scryfall: ScryfallMTGCard = match serde_json::from_value(scryfall) {
+        Ok(card) => card,
+        Err(err) => {
+            error!("[ MTG ] Unable to read the card data from Scryfall: {err}");
+            return Err(Error::Scryfall("There was an error processing your request!".to_string()));
+        }
+    };
 
     let card_embed: Vec<serenity::CreateEmbed> = if scryfall.card_faces.is_some() {
         create_double_face_embed(scryfall.clone())
@@ -1,62 +1,146 @@
-use crate::{data::user_table_check, Context, Error};
+use crate::{data::user_table_check, pagination::{paginate, split_into_pages}, Context, Data, Error};
+use crate::storage::Storage;
+use crate::storage::guild_settings::QotdGuild;
+use crate::storage::quotes::RatedQuote;
+use chrono::{NaiveDate, Timelike, Utc};
 use futures::future;
+use std::collections::HashMap;
 use poise::serenity_prelude as serenity;
+use tracing::{info, error};
 
 //--------------------
 // Data
 //--------------------
 #[derive(Default, sqlx::FromRow)]
-struct Quote {
-    guild_id: u64,
-    adder_id: u64,
-    sayer_id: u64,
-    quote_id: u32,
-    quote: String,
-    timestamp: chrono::NaiveDate,
-    sayer_display_name: String,
-    adder_display_name: String,
+pub struct Quote {
+    pub guild_id: u64,
+    pub adder_id: u64,
+    pub sayer_id: u64,
+    pub quote_id: u32,
+    pub quote: String,
+    pub timestamp: chrono::NaiveDate,
+    pub sayer_display_name: String,
+    pub adder_display_name: String,
+    // Only set for quotes saved from a message
+    pub message_link: Option<String>,
+    pub channel_id: Option<u64>,
+    // Unix timestamp, deleted quotes are kept so they can be restored
+    pub deleted_at: Option<i64>,
+    pub deleted_by: Option<u64>,
 }
 
+// One line of a conversation quote. The quote's sayer is the first speaker.
+#[derive(sqlx::FromRow)]
+pub struct QuoteLine {
+    pub speaker_id: u64,
+    pub speaker_display_name: String,
+    pub line: String,
+}
+
+// The state of a quote before one of its edits
+#[derive(sqlx::FromRow)]
+pub struct QuoteRevision {
+    pub editor_id: u64,
+    pub edited_at: i64,
+    pub old_quote: String,
+    pub old_sayer_display_name: String,
+    pub old_timestamp: chrono::NaiveDate,
+}
+
+// Upvote button ids are this followed by the quote ID. They are handled in the event listener so they keep working after a restart.
+pub const UPVOTE_PREFIX: &str = "quote_upvote:";
+
 //--------------------
 // Functions
 //--------------------
-async fn build_single_quote_embed(http: &serenity::Http, quote: Quote) -> serenity::CreateEmbed {
+async fn build_single_quote_embed(http: &serenity::Http, database: &Storage, quote: Quote) -> Result<serenity::CreateEmbed, Error> {
     // Get serenity user
-    let sayer = serenity::UserId::new(quote.sayer_id).to_user(http).await.unwrap();
+    let sayer = serenity::UserId::new(quote.sayer_id).to_user(http).await?;
+    let lines = database.get_quote_lines(quote.guild_id, quote.quote_id).await?;
+    let tags = database.get_quote_tags(quote.guild_id, quote.quote_id).await?;
 
     // Build embed
-    let title = format!("Quote #{} by {}", quote.quote_id, quote.sayer_display_name);
+    let title = if lines.is_empty() {
+        format!("Quote #{} by {}", quote.quote_id, quote.sayer_display_name)
+    } else {
+        format!("Quote #{} between {}", quote.quote_id, conversation_speakers(&lines))
+    };
     let footer = serenity::CreateEmbedFooter::new(format!("Added by {} on {}", quote.adder_display_name, quote.timestamp));
 
-    serenity::CreateEmbed::new()
+    // Conversations are shown as a dialogue, one line per speaker
+    let text = if lines.is_empty() {
+        quote.quote
+    } else {
+        lines.iter()
+            .map(|l| format!("**{}:** {}", l.speaker_display_name, l.line))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    // Link back to the original message if the quote came from one
+    let description = match &quote.message_link {
+        Some(link) => format!("{text}\n\n[Jump to message]({link})"),
+        None => text
+    };
+
+    let mut embed = serenity::CreateEmbed::new()
         .colour(0x0b4a6f)
-        .description(quote.quote)
+        .description(description)
         .title(title)
         .thumbnail(sayer.face())
-        .footer(footer)
+        .footer(footer);
+
+    if let Some(link) = quote.message_link {
+        embed = embed.url(link);
+    }
+
+    if !tags.is_empty() {
+        embed = embed.field("Tags", tags.join(", "), false);
+    }
+
+    Ok(embed)
+}
+
+fn build_upvote_button(quote_id: u32, votes: i64) -> serenity::CreateActionRow {
+    let button = serenity::CreateButton::new(format!("{UPVOTE_PREFIX}{quote_id}"))
+        .emoji('👍')
+        .label(votes.to_string());
+
+    serenity::CreateActionRow::Buttons(vec![button])
+}
+
+// Every speaker in a conversation once, in the order they first speak
+fn conversation_speakers(lines: &[QuoteLine]) -> String {
+    let mut names: Vec<&str> = Vec::new();
+    for line in lines {
+        if !names.contains(&line.speaker_display_name.as_str()) {
+            names.push(&line.speaker_display_name);
+        }
+    }
+
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+        _ => names.concat()
+    }
 }
 
 // Check if the quote command requires a role and determine if the command can be used
 async fn quote_role_check(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().unwrap().get();
-    let role_id = sqlx::query!("SELECT quotes_required_role FROM guild_settings WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .quotes_required_role;
+    let role_id = ctx.data().database.get_quotes_required_role(guild_id).await?;
 
     if role_id.is_none() {
         return Ok(true);
     }
 
     let role_id = serenity::RoleId::new(role_id.unwrap());
-    let guild_roles = ctx.guild_id().unwrap().roles(&ctx.http()).await.unwrap();
+    let guild_roles = ctx.guild_id().unwrap().roles(&ctx.http()).await?;
     let role = match guild_roles.get(&role_id) {
         Some(r) => r,
-        None => return Err("A role is set to be required for this command, but it doesn't exist!".into())
+        None => return Err(Error::Permission("A role is set to be required for this command, but it doesn't exist!".to_string()))
     };
 
-    if !ctx.author().has_role(ctx.http(), guild_id, role_id).await.unwrap() {
+    if !ctx.author().has_role(ctx.http(), guild_id, role_id).await? {
         ctx.send(
     poise::CreateReply::default()
                 .content(format!("You must have the '{}' role to run this command!", role.name))
@@ -69,33 +153,130 @@ async fn quote_role_check(ctx: Context<'_>) -> Result<bool, Error> {
     Ok(true)
 }
 
-fn split_quotes_into_pages(guild_quotes: Vec<Quote>) -> Vec<String> {
-    let mut page_content = String::new();
-    let mut pages: Vec<String> = Vec::new();
-    for (i, quote) in guild_quotes.iter().enumerate() {
-        page_content = format!("{page_content}**{}.** {} \n*\\- {} {}* (ID: {})\n\n",
-            i + 1,
-            quote.quote,
-            quote.sayer_display_name,
-            quote.timestamp,
-            quote.quote_id,
-        );
-
-        // Split content into more pages as necessary
-        if i + 1 == guild_quotes.len() {
-            pages.push(page_content);
-            break;
+// Split search text into words, keeping "double quoted" phrases together
+fn parse_search_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+
+    for (i, part) in text.split('"').enumerate() {
+        // Every odd part was inside quotes
+        if i % 2 == 1 {
+            let phrase = part.trim();
+            if !phrase.is_empty() {
+                terms.push(phrase.to_string());
+            }
+        } else {
+            terms.extend(part.split_whitespace().map(String::from));
         }
+    }
 
-        if (i + 1) % 5 == 0 {
-            pages.push(page_content);
-            page_content = String::new();
+    terms
+}
+
+// Split comma separated tags, ignoring case and blanks
+fn parse_tags(text: &str) -> Result<Vec<String>, Error> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in text.split(',').map(|t| t.trim().to_lowercase()) {
+        if tag.is_empty() || tags.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > 32 {
+            return Err(format!("The tag `{tag}` is too long! Tags can be up to 32 characters.").into());
         }
+
+        tags.push(tag);
+    }
+
+    if tags.is_empty() {
+        return Err("You must enter at least one tag!".into());
+    }
+    if tags.len() > 10 {
+        return Err("You can only add up to 10 tags at a time!".into());
+    }
+
+    Ok(tags)
+}
+
+// Suggests tags already used in the guild for the last tag in a comma separated list
+async fn autocomplete_tags(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let (done, current) = match partial.rsplit_once(',') {
+        Some((done, current)) => (format!("{done}, "), current),
+        None => (String::new(), partial)
+    };
+
+    let tags = ctx.data().database.search_quote_tags(guild_id, &current.trim().to_lowercase()).await.unwrap_or_default();
+
+    tags.into_iter()
+        .map(|tag| format!("{done}{tag}"))
+        .filter(|choice| choice.chars().count() <= 100) // Discord's limit for autocomplete choice names
+        .collect()
+}
+
+// Tags of every quote in the guild by quote ID
+async fn get_tags_by_quote(ctx: Context<'_>, guild_id: u64) -> Result<HashMap<u32, Vec<String>>, Error> {
+    let mut tags: HashMap<u32, Vec<String>> = HashMap::new();
+    for (quote_id, tag) in ctx.data().database.get_guild_quote_tags(guild_id).await? {
+        tags.entry(quote_id).or_default().push(tag);
     }
 
-    pages
+    Ok(tags)
+}
+
+fn format_quote_line(i: usize, quote: &Quote, tags: &HashMap<u32, Vec<String>>) -> String {
+    let quote_tags = match tags.get(&quote.quote_id) {
+        Some(t) => format!("\n:label: {}", t.join(", ")),
+        None => String::new()
+    };
+
+    format!("**{}.** {} \n*\\- {} {}* (ID: {}){quote_tags}\n\n",
+        i + 1,
+        quote.quote,
+        quote.sayer_display_name,
+        quote.timestamp,
+        quote.quote_id,
+    )
 }
 
+fn format_top_quote_line(i: usize, rated: &RatedQuote) -> String {
+    format!("**{}.** {} \n*\\- {} {}* (ID: {}) - :thumbsup: {}\n\n",
+        i + 1,
+        rated.quote.quote,
+        rated.quote.sayer_display_name,
+        rated.quote.timestamp,
+        rated.quote.quote_id,
+        rated.votes,
+    )
+}
+
+fn format_most_quoted_line(i: usize, (member_id, times_quoted): &(u64, i64)) -> String {
+    format!("**{}.** <@{member_id}> - {times_quoted} quote{}\n",
+        i + 1,
+        if *times_quoted == 1 { "" } else { "s" },
+    )
+}
+
+// Revisions are listed newest first, so they're numbered from the end
+fn format_revision_line(revision_number: usize, revision: &QuoteRevision) -> String {
+    format!("**Before edit {revision_number}** by <@{}> <t:{}:f>\n{} \n*\\- {} {}*\n\n",
+        revision.editor_id,
+        revision.edited_at,
+        revision.old_quote,
+        revision.old_sayer_display_name,
+        revision.old_timestamp,
+    )
+}
+
+fn format_deleted_quote_line(_: usize, quote: &Quote) -> String {
+    format!("**#{}** {} \n*\\- {} {}*\nDeleted by <@{}> <t:{}:R>\n\n",
+        quote.quote_id,
+        quote.quote,
+        quote.sayer_display_name,
+        quote.timestamp,
+        quote.deleted_by.unwrap_or_default(),
+        quote.deleted_at.unwrap_or_default(),
+    )
+}
 
 //--------------------
 // Commands
@@ -112,18 +293,27 @@ pub async fn addquote(
     #[description = "The person who said the quote."] sayer: serenity::Member,
     #[max_length = 500]
     #[description = "The quote to record."] quote: String,
-    #[description = "Optionally add a date for the quote."] date: Option<String>
+    #[description = "Optionally add a date for the quote."] date: Option<String>,
+    #[description = "Optionally tag the quote, separating tags with commas."]
+    #[autocomplete = "autocomplete_tags"] tags: Option<String>
 ) -> Result<(), Error> {
+    let tags = match tags {
+        Some(t) => parse_tags(&t)?,
+        None => Vec::new()
+    };
+
     ctx.defer().await?;
     
     // Build quote then insert
-    let timestamp = if date.is_some() {
-        match chrono::NaiveDate::parse_from_str(&date.unwrap(), "%F") {
+    let timestamp = match date {
+        Some(date) => match chrono::NaiveDate::parse_from_str(&date, "%F") {
             Ok(d) => d,
             Err(_) => return Err("You must formt the date with YYYY-MM-DD!".into())
+        },
+        None => {
+            let timezone = ctx.data().database.get_timezone(ctx.guild_id().unwrap().get()).await?;
+            chrono::Utc::now().with_timezone(&timezone).date_naive()
         }
-    } else {
-        chrono::Utc::now().date_naive()
     };
 
     let mut quote_data = Quote {
@@ -137,121 +327,622 @@ pub async fn addquote(
         ..Default::default()
     };
 
-    let max_quote_id = sqlx::query!("SELECT MAX(quote_id) AS quote_id FROM quotes WHERE guild_id = ?", quote_data.guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .quote_id
-        .unwrap_or(0);
+    let max_quote_id = ctx.data().database.get_max_quote_id(quote_data.guild_id).await?;
     quote_data.quote_id = max_quote_id + 1;
 
-    let insert_query = sqlx::query!("INSERT INTO quotes (guild_id, adder_id, sayer_id, quote_id, quote, timestamp, adder_display_name, sayer_display_name) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-            quote_data.guild_id,
-            quote_data.adder_id,
-            quote_data.sayer_id,
-            quote_data.quote_id,
-            quote_data.quote,
-            quote_data.timestamp,
-            quote_data.adder_display_name,
-            quote_data.sayer_display_name
-        )
-        .execute(&ctx.data().database);
+    let insert_query = ctx.data().database.insert_quote(&quote_data);
     let sayer_check = user_table_check(ctx, &sayer.user);
-    let quote_embed = build_single_quote_embed(ctx.http(), quote_data);
     
-    let futures = future::join3(insert_query, sayer_check, quote_embed).await;
+    let (insert_result, sayer_result) = future::join(insert_query, sayer_check).await;
+    insert_result?;
+    sayer_result?;
+
+    if !tags.is_empty() {
+        ctx.data().database.add_quote_tags(quote_data.guild_id, quote_data.quote_id, &tags).await?;
+    }
+
+    let quote_embed = build_single_quote_embed(ctx.http(), &ctx.data().database, quote_data).await?;
 
     // Build embed then post success
     ctx.send(
         poise::CreateReply::default()
             .content("Quote successfully added!")
-            .embed(futures.2)
+            .embed(quote_embed)
     ).await?;
 
     Ok(())
 }
 
+/// Add a conversation between several people as one quote
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn addconversation(
+    ctx: Context<'_>,
+    #[description = "Who said the first line."] speaker1: serenity::Member,
+    #[max_length = 200]
+    #[description = "The first line."] line1: String,
+    #[description = "Who said the second line."] speaker2: serenity::Member,
+    #[max_length = 200]
+    #[description = "The second line."] line2: String,
+    #[description = "Who said the third line."] speaker3: Option<serenity::Member>,
+    #[max_length = 200]
+    #[description = "The third line."] line3: Option<String>,
+    #[description = "Who said the fourth line."] speaker4: Option<serenity::Member>,
+    #[max_length = 200]
+    #[description = "The fourth line."] line4: Option<String>,
+    #[description = "Who said the fifth line."] speaker5: Option<serenity::Member>,
+    #[max_length = 200]
+    #[description = "The fifth line."] line5: Option<String>,
+    #[description = "Optionally add a date for the quote."] date: Option<String>,
+    #[description = "Optionally tag the quote, separating tags with commas."]
+    #[autocomplete = "autocomplete_tags"] tags: Option<String>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let tags = match tags {
+        Some(t) => parse_tags(&t)?,
+        None => Vec::new()
+    };
+
+    // Pair each speaker with their line, a speaker without a line or a line without a speaker is a mistake
+    let mut pairs = vec![(speaker1, line1), (speaker2, line2)];
+    for (speaker, line) in [(speaker3, line3), (speaker4, line4), (speaker5, line5)] {
+        match (speaker, line) {
+            (Some(s), Some(l)) => pairs.push((s, l)),
+            (None, None) => {},
+            _ => return Err("Every line needs a speaker and every speaker needs a line!".into())
+        }
+    }
+
+    let lines: Vec<QuoteLine> = pairs.iter()
+        .map(|(speaker, line)| QuoteLine {
+            speaker_id: speaker.user.id.get(),
+            speaker_display_name: String::from(speaker.display_name()),
+            line: line.clone(),
+        })
+        .collect();
+
+    // The plain text version is what searches and quote lists see
+    let quote = lines.iter()
+        .map(|l| format!("{}: {}", l.speaker_display_name, l.line))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    if quote.chars().count() > 500 {
+        return Err("That conversation is too long! Conversations can be up to 500 characters including names.".into());
+    }
+
+    ctx.defer().await?;
+
+    let timestamp = match date {
+        Some(date) => match chrono::NaiveDate::parse_from_str(&date, "%F") {
+            Ok(d) => d,
+            Err(_) => return Err("You must formt the date with YYYY-MM-DD!".into())
+        },
+        None => {
+            let timezone = ctx.data().database.get_timezone(guild_id).await?;
+            chrono::Utc::now().with_timezone(&timezone).date_naive()
+        }
+    };
+
+    let mut quote_data = Quote {
+        guild_id,
+        adder_id: ctx.author().id.get(),
+        sayer_id: lines[0].speaker_id,
+        quote,
+        timestamp,
+        sayer_display_name: lines[0].speaker_display_name.clone(),
+        adder_display_name: String::from(ctx.author().display_name()),
+        ..Default::default()
+    };
+
+    let max_quote_id = ctx.data().database.get_max_quote_id(guild_id).await?;
+    quote_data.quote_id = max_quote_id + 1;
+
+    ctx.data().database.insert_conversation_quote(&quote_data, &lines).await?;
+
+    for (speaker, _) in &pairs {
+        user_table_check(ctx, &speaker.user).await?;
+    }
+
+    if !tags.is_empty() {
+        ctx.data().database.add_quote_tags(guild_id, quote_data.quote_id, &tags).await?;
+    }
+
+    let quote_embed = build_single_quote_embed(ctx.http(), &ctx.data().database, quote_data).await?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content("Quote successfully added!")
+            .embed(quote_embed)
+    ).await?;
+
+    Ok(())
+}
+
+/// Save a message as a quote
+#[poise::command(
+    context_menu_command = "Quote this message",
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+pub async fn quote_message(
+    ctx: Context<'_>,
+    message: serenity::Message
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    if message.content.trim().is_empty() {
+        return Err("That message doesn't have any text to quote!".into());
+    }
+    if message.content.chars().count() > 500 {
+        return Err("That message is too long to quote! Quotes can be up to 500 characters.".into());
+    }
+    if message.author.bot {
+        return Err("You can't quote a bot!".into());
+    }
+
+    let message_link = message.link();
+    if let Some(existing) = ctx.data().database.find_quote_by_link(guild_id.get(), &message_link).await? {
+        let msg = if existing.deleted_at.is_some() {
+            format!("That message was already quoted but the quote was deleted! Bring it back with `/quote restore {}`.", existing.quote_id)
+        } else {
+            format!("That message has already been quoted! (ID: {})", existing.quote_id)
+        };
+        return Err(msg.into());
+    }
+
+    ctx.defer().await?;
+
+    // Use the name the sayer had on the server if they're still a member
+    let sayer_display_name = match guild_id.member(ctx, message.author.id).await {
+        Ok(m) => m.display_name().to_string(),
+        Err(_) => message.author.display_name().to_string()
+    };
+
+    let timezone = ctx.data().database.get_timezone(guild_id.get()).await?;
+
+    let mut quote_data = Quote {
+        guild_id: guild_id.get(),
+        adder_id: ctx.author().id.get(),
+        sayer_id: message.author.id.get(),
+        quote: message.content.clone(),
+        timestamp: message.timestamp.with_timezone(&timezone).date_naive(),
+        sayer_display_name,
+        adder_display_name: String::from(ctx.author().display_name()),
+        message_link: Some(message_link),
+        channel_id: Some(message.channel_id.get()),
+        ..Default::default()
+    };
+
+    let max_quote_id = ctx.data().database.get_max_quote_id(quote_data.guild_id).await?;
+    quote_data.quote_id = max_quote_id + 1;
+
+    let insert_query = ctx.data().database.insert_quote(&quote_data);
+    let sayer_check = user_table_check(ctx, &message.author);
+
+    let (insert_result, sayer_result) = future::join(insert_query, sayer_check).await;
+    insert_result?;
+    sayer_result?;
+
+    let quote_embed = build_single_quote_embed(ctx.http(), &ctx.data().database, quote_data).await?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content("Quote successfully added!")
+            .embed(quote_embed)
+    ).await?;
+
+    Ok(())
+}
+
+/// Pull quotes from the database
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    subcommands("get", "search", "top", "leaderboard", "tag", "untag", "edit", "history", "restore", "deleted", "daily")
+)]
+pub async fn quote(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
 /// Get quote from the database
 #[poise::command(
     slash_command,
     guild_only,
     member_cooldown = 5
 )]
-pub async fn quote(
+pub async fn get(
     ctx: Context<'_>,
     #[description = "Search for a specific quote by ID."] id: Option<u32>,
     #[description = "Grab a random quote said by a user."] user: Option<serenity::User>,
     #[description = "Grab a quote that contains the given text."] text: Option<String>,
+    #[description = "Grab a random quote with a tag. Can be used along with a user."]
+    #[autocomplete = "autocomplete_tags"] tag: Option<String>,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
     // Determine query and run it
-    let query = if id.is_some() {
-        format!("SELECT * FROM quotes WHERE guild_id = {} AND quote_id = {}", guild_id, id.unwrap())
-    } else if user.is_some() {
-        format!("SELECT * FROM quotes WHERE guild_id = {} AND sayer_id = {} ORDER BY RAND() LIMIT 1", guild_id, user.unwrap().id.get())
-    } else if text.is_some() {
-        format!("SELECT * FROM quotes WHERE guild_id = {} AND quote LIKE \"%{}%\"", guild_id, text.unwrap())
+    let database = &ctx.data().database;
+    let quote = if let Some(id) = id {
+        database.get_quote(guild_id, id).await?
+    } else if user.is_some() || tag.is_some() {
+        let tag = tag.map(|t| t.trim().to_lowercase());
+        database.get_random_quote(guild_id, user.map(|u| u.id.get()), tag.as_deref()).await?
+    } else if let Some(text) = text {
+        let mut matches = database.search_quotes(guild_id, &[text], None).await?;
+
+        // List every match so the right one can be pulled up by ID
+        if matches.len() > 1 {
+            let title = format!("{} Quotes Contain That Text", matches.len());
+            let tags = get_tags_by_quote(ctx, guild_id).await?;
+            let pages = split_into_pages(&matches, 5, |i, quote| format_quote_line(i, quote, &tags));
+
+            paginate(ctx, &title, 0x0b4a6f, &pages).await?;
+
+            return Ok(());
+        }
+
+        matches.pop()
     } else {
-        format!("SELECT * FROM quotes WHERE guild_id = {} ORDER BY RAND() LIMIT 1", guild_id)
+        database.get_random_quote(guild_id, None, None).await?
     };
-    
-    let quote = sqlx::query_as(&query)
-        .fetch_one(&ctx.data().database)
-        .await;
 
     let quote = match quote {
-        Ok(q) => q,
-        Err(_) => return Err("Unable to find that quote in the database!".into())
+        Some(q) => q,
+        None => return Err("Unable to find that quote in the database!".into())
     };
 
-    // Send quote
-    let quote = build_single_quote_embed(ctx.http(), quote).await;
-    ctx.send(poise::CreateReply::default().embed(quote)).await?;
+    // Send quote with its upvote button
+    let votes = ctx.data().database.count_quote_votes(guild_id, quote.quote_id).await?;
+    let upvote_button = build_upvote_button(quote.quote_id, votes);
+
+    let quote = build_single_quote_embed(ctx.http(), &ctx.data().database, quote).await?;
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(quote)
+            .components(vec![upvote_button])
+    ).await?;
 
     Ok(())
 }
 
-/// Delete quote from the database
+/// Search quotes for one or more words
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5
+)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Words the quote must contain. Use \"double quotes\" to search for a phrase."]
+    #[max_length = 200] text: String,
+    #[description = "Only search quotes said by this user."] user: Option<serenity::User>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let terms = parse_search_terms(&text);
+    if terms.is_empty() {
+        return Err("You must enter something to search for!".into());
+    }
+    if terms.len() > 10 {
+        return Err("You can only search for up to 10 words or phrases at a time!".into());
+    }
+
+    let sayer_id = user.map(|u| u.id.get());
+    let guild_quotes = ctx.data().database.search_quotes(guild_id, &terms, sayer_id).await?;
+
+    if guild_quotes.is_empty() {
+        return Err("No quotes found matching your search!".into());
+    }
+
+    let title = format!("Quote Search - {} Result{}", guild_quotes.len(), if guild_quotes.len() == 1 { "" } else { "s" });
+    let tags = get_tags_by_quote(ctx, guild_id).await?;
+    let pages = split_into_pages(&guild_quotes, 5, |i, quote| format_quote_line(i, quote, &tags));
+
+    paginate(ctx, &title, 0x0b4a6f, &pages).await?;
+
+    Ok(())
+}
+
+/// List the highest rated quotes
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5
+)]
+pub async fn top(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let top_quotes = ctx.data().database.get_top_quotes(guild_id).await?;
+
+    if top_quotes.is_empty() {
+        return Err("No quotes have been upvoted yet!".into());
+    }
+
+    let pages = split_into_pages(&top_quotes, 5, format_top_quote_line);
+
+    paginate(ctx, "Top Quotes", 0x0b4a6f, &pages).await?;
+
+    Ok(())
+}
+
+/// List the most quoted members
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5
+)]
+pub async fn leaderboard(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let most_quoted = ctx.data().database.get_most_quoted(guild_id).await?;
+
+    if most_quoted.is_empty() {
+        return Err("No quotes found!".into());
+    }
+
+    let pages = split_into_pages(&most_quoted, 15, format_most_quoted_line);
+
+    paginate(ctx, "Most Quoted Members", 0x0b4a6f, &pages).await?;
+
+    Ok(())
+}
+
+/// Add tags to a quote
 #[poise::command(
     slash_command,
     guild_only,
     member_cooldown = 5,
     check = "quote_role_check",
 )]
-pub async fn delquote(
+pub async fn tag(
     ctx: Context<'_>,
-    #[description = "The ID of the quote to delete."] id: u32
+    #[description = "The ID of the quote to tag."] id: u32,
+    #[description = "The tags to add, separated by commas."]
+    #[autocomplete = "autocomplete_tags"] tags: String
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let tags = parse_tags(&tags)?;
+
+    if ctx.data().database.get_quote(guild_id, id).await?.is_none() {
+        return Err("No quote saved with that ID!".into());
+    }
+
+    ctx.data().database.add_quote_tags(guild_id, id, &tags).await?;
+
+    let quote_tags = ctx.data().database.get_quote_tags(guild_id, id).await?;
+    ctx.say(format!("Quote #{id} is now tagged with: {}", quote_tags.join(", "))).await?;
+
+    Ok(())
+}
+
+/// Remove a tag from a quote
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+pub async fn untag(
+    ctx: Context<'_>,
+    #[description = "The ID of the quote."] id: u32,
+    #[description = "The tag to remove."]
+    #[autocomplete = "autocomplete_tags"] tag: String
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
+    let tag = tag.trim().to_lowercase();
+
+    if !ctx.data().database.remove_quote_tag(guild_id, id, &tag).await? {
+        return Err("That quote doesn't have that tag!".into());
+    }
+
+    ctx.say(format!("Removed the `{tag}` tag from quote #{id}!")).await?;
 
-    // Check if quote exists first
-    let quote = sqlx::query_as!(Quote, "SELECT * FROM quotes WHERE guild_id = ? AND quote_id = ?", guild_id, id)
-        .fetch_optional(&ctx.data().database)
-        .await
-        .unwrap();
+    Ok(())
+}
 
-    let _ = match quote {
+/// Edit a quote's text, sayer or date
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+pub async fn edit(
+    ctx: Context<'_>,
+    #[description = "The ID of the quote to edit."] id: u32,
+    #[max_length = 500]
+    #[description = "The corrected quote."] text: Option<String>,
+    #[description = "The person who actually said the quote."] sayer: Option<serenity::Member>,
+    #[description = "The corrected date, formatted YYYY-MM-DD."] date: Option<String>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    if text.is_none() && sayer.is_none() && date.is_none() {
+        return Err("You must change the text, sayer or date!".into());
+    }
+
+    let mut quote = match ctx.data().database.get_quote(guild_id, id).await? {
         Some(q) => q,
         None => return Err("No quote saved with that ID!".into())
     };
 
-    // Delete quote
-    let delete_query_str = format!("DELETE FROM quotes WHERE guild_id = {guild_id} AND quote_id = {id};
-        UPDATE quotes SET quote_id = quote_id - 1 WHERE guild_id = {guild_id} AND quote_id = {id}");
-        
-    let delete_query = sqlx::raw_sql(&delete_query_str)
-        .execute(&ctx.data().database)
-        .await;
-        
-    let _ = match delete_query {
-        Ok(_) => ctx.say("Successfully deleted the quote!").await.unwrap(),
-        Err(e) => {
-            println!("{e}");
-            ctx.say("There was an error trying to delete the quote!").await.unwrap()
-        }
+    // The text and sayer of a conversation come from its lines
+    if (text.is_some() || sayer.is_some()) && !ctx.data().database.get_quote_lines(guild_id, id).await?.is_empty() {
+        return Err("Only the date of a conversation can be edited!".into());
+    }
+
+    if let Some(text) = text {
+        quote.quote = text;
+    }
+    if let Some(date) = date {
+        quote.timestamp = match chrono::NaiveDate::parse_from_str(&date, "%F") {
+            Ok(d) => d,
+            Err(_) => return Err("You must formt the date with YYYY-MM-DD!".into())
+        };
+    }
+    if let Some(sayer) = &sayer {
+        quote.sayer_id = sayer.user.id.get();
+        quote.sayer_display_name = String::from(sayer.display_name());
+        user_table_check(ctx, &sayer.user).await?;
+    }
+
+    ctx.data().database.edit_quote(&quote, ctx.author().id.get(), chrono::Utc::now().timestamp()).await?;
+
+    let quote_embed = build_single_quote_embed(ctx.http(), &ctx.data().database, quote).await?;
+
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("Quote successfully edited! See every change with `/quote history {id}`."))
+            .embed(quote_embed)
+    ).await?;
+
+    Ok(())
+}
+
+/// Show every edit made to a quote
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5
+)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "The ID of the quote."] id: u32
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let quote = match ctx.data().database.get_quote(guild_id, id).await? {
+        Some(q) => q,
+        None => return Err("No quote saved with that ID!".into())
     };
 
+    let revisions = ctx.data().database.get_quote_revisions(guild_id, id).await?;
+
+    if revisions.is_empty() {
+        return Err("That quote has never been edited!".into());
+    }
+
+    // Newest first, starting with the quote as it is now
+    let revision_count = revisions.len();
+    let revisions: Vec<QuoteRevision> = revisions.into_iter().rev().collect();
+    let mut pages = split_into_pages(&revisions, 5, |i, revision| format_revision_line(revision_count - i, revision));
+    pages[0] = format!("**Current**\n{} \n*\\- {} {}*\n\n{}", quote.quote, quote.sayer_display_name, quote.timestamp, pages[0]);
+
+    paginate(ctx, &format!("Quote #{id} History"), 0x0b4a6f, &pages).await?;
+
+    Ok(())
+}
+
+/// Restore a deleted quote
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+pub async fn restore(
+    ctx: Context<'_>,
+    #[description = "The ID of the quote to restore."] id: u32
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    if !ctx.data().database.restore_quote(guild_id, id).await? {
+        return Err("No deleted quote with that ID!".into());
+    }
+
+    ctx.say(format!("Successfully restored quote #{id}!")).await?;
+
+    Ok(())
+}
+
+/// List deleted quotes, most recently deleted first
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    required_permissions = "MANAGE_CHANNELS",
+    ephemeral
+)]
+pub async fn deleted(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    let deleted_quotes = ctx.data().database.list_deleted_quotes(guild_id).await?;
+
+    if deleted_quotes.is_empty() {
+        return Err("No deleted quotes found!".into());
+    }
+
+    let pages = split_into_pages(&deleted_quotes, 5, format_deleted_quote_line);
+
+    paginate(ctx, "Deleted Quotes", 0x0b4a6f, &pages).await?;
+
+    Ok(())
+}
+
+/// Set up or turn off the quote of the day
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    required_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn daily(
+    ctx: Context<'_>,
+    #[description = "Channel to post the quote of the day in. Leave blank to turn it off."] channel: Option<serenity::Channel>,
+    #[description = "Hour to post at in the server's timezone. Leave blank to keep the current one (12 by default)."]
+    #[min = 0_u8]
+    #[max = 23_u8] hour: Option<u8>
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    // Handle empty channel (disables the quote of the day)
+    if channel.is_none() {
+        ctx.data().database.set_qotd(guild_id, None, hour).await?;
+
+        ctx.say(format!("{}, no longer posting a quote of the day!", ctx.author())).await?;
+        return Ok(());
+    }
+
+    let channel_id = channel.as_ref().unwrap().id().get();
+
+    ctx.data().database.set_qotd(guild_id, Some(channel_id), hour).await?;
+    let hour = ctx.data().database.get_qotd_hour(guild_id).await?;
+
+    ctx.say(format!("{}, now posting a quote of the day in {} at {hour:02}:00!", ctx.author(), channel.unwrap())).await?;
+
+    Ok(())
+}
+
+/// Delete quote from the database
+#[poise::command(
+    slash_command,
+    guild_only,
+    member_cooldown = 5,
+    check = "quote_role_check",
+)]
+pub async fn delquote(
+    ctx: Context<'_>,
+    #[description = "The ID of the quote to delete."] id: u32
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap().get();
+
+    // Quotes are only marked as deleted so IDs people refer to never change
+    let deleted = ctx.data().database.delete_quote(guild_id, id, ctx.author().id.get(), chrono::Utc::now().timestamp()).await?;
+
+    if !deleted {
+        return Err("No quote saved with that ID!".into());
+    }
+
+    ctx.say(format!("Successfully deleted the quote! It can be brought back with `/quote restore {id}`.")).await?;
+
     Ok(())
 }
 
@@ -268,23 +959,17 @@ pub async fn setquoterole(
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    if role.is_none() { // Remove role requirement
-        sqlx::query!("UPDATE guild_settings SET quotes_required_role = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
-
-        ctx.say("No longer requiring a role to modify quotes!").await?;
-    } else { // Add/modify role requirement
-        let role_id = role.as_ref().unwrap().id.get();
-        let role_name = role.unwrap().name;
+    match role {
+        None => { // Remove role requirement
+            ctx.data().database.set_quotes_required_role(guild_id, None).await?;
 
-        sqlx::query!("UPDATE guild_settings SET quotes_required_role = ? WHERE guild_id = ?", role_id, guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+            ctx.say("No longer requiring a role to modify quotes!").await?;
+        },
+        Some(role) => { // Add/modify role requirement
+            ctx.data().database.set_quotes_required_role(guild_id, Some(role.id.get())).await?;
 
-        ctx.say(format!("Now requiring the {role_name} role to modify quotes!")).await?;
+            ctx.say(format!("Now requiring the {} role to modify quotes!", role.name)).await?;
+        }
     }
 
     Ok(())
@@ -298,81 +983,143 @@ pub async fn setquoterole(
 )]
 pub async fn listquotes(
     ctx: Context<'_>,
-    #[description = "Pull all quotes from a user."] user: Option<serenity::User>
+    #[description = "Pull all quotes from a user."] user: Option<serenity::User>,
+    #[description = "Only list quotes with this tag."]
+    #[autocomplete = "autocomplete_tags"] tag: Option<String>
 ) -> Result<(), Error> {
     // Grab sorted guild quotes into vector
     let guild_id = ctx.guild_id().unwrap().get();
     
-    let user_id = match user {
-        Some(u) => u.id.get(),
-        None => 0
-    };
+    let user_id = user.map(|u| u.id.get());
+    let tag = tag.map(|t| t.trim().to_lowercase());
 
-    let guild_quotes: Vec<Quote> = if user_id == 0 {
-        sqlx::query_as!(Quote, "SELECT * FROM quotes WHERE guild_id = ? ORDER BY quote_id", guild_id)
-            .fetch_all(&ctx.data().database)
-            .await
-            .unwrap()
-    } else {
-        sqlx::query_as!(Quote, "SELECT * FROM quotes WHERE guild_id = ? AND sayer_id = ? ORDER BY quote_id", guild_id, user_id)
-            .fetch_all(&ctx.data().database)
-            .await
-            .unwrap()
-    };
+    let guild_quotes = ctx.data().database.list_quotes(guild_id, user_id, tag.as_deref()).await?;
 
     if guild_quotes.is_empty() {
         return Err("No quotes found!".into());
     }
 
-    // Create initial embed
-    let pages = split_quotes_into_pages(guild_quotes);
-    let mut page_num = 0;
-    let ctx_id = ctx.id();
-    let prev_id = format!("{ctx_id}prev");
-    let next_id = format!("{ctx_id}next");
+    let tags = get_tags_by_quote(ctx, guild_id).await?;
+    let pages = split_into_pages(&guild_quotes, 5, |i, quote| format_quote_line(i, quote, &tags));
 
-    let buttons: Vec<serenity::CreateButton> = vec![
-        serenity::CreateButton::new(&prev_id).label("Previous"),
-        serenity::CreateButton::new(&next_id).label("Next")
-    ];
-    let buttons = serenity::CreateActionRow::Buttons(buttons);
+    paginate(ctx, "Quotes", 0x0b4a6f, &pages).await?;
 
-    let embed = serenity::CreateEmbed::new()
-        .description(&pages[page_num])
-        .colour(0x0b4a6f)
-        .title("Quotes");
+    Ok(())
+}
+
+//--------------------------
+// Upvotes
+//--------------------------
+// Pressing the upvote button adds the member's vote, pressing it again takes it back
+pub async fn handle_upvote(ctx: &serenity::Context, data: &Data, press: &serenity::ComponentInteraction) -> Result<(), Error> {
+    let Some(guild_id) = press.guild_id else {
+        return Ok(());
+    };
+    let Ok(quote_id) = press.data.custom_id[UPVOTE_PREFIX.len()..].parse::<u32>() else {
+        return Ok(());
+    };
 
-    ctx.send(poise::CreateReply::default()
-        .embed(embed)
-        .components(vec![buttons])
+    if data.database.get_quote(guild_id.get(), quote_id).await?.is_none() {
+        press.create_response(
+            ctx,
+            serenity::CreateInteractionResponse::Message(
+                serenity::CreateInteractionResponseMessage::new()
+                    .content("That quote has been deleted!")
+                    .ephemeral(true)
+            )
+        ).await?;
+
+        return Ok(());
+    }
+
+    data.database.toggle_quote_vote(guild_id.get(), quote_id, press.user.id.get()).await?;
+    let votes = data.database.count_quote_votes(guild_id.get(), quote_id).await?;
+
+    press.create_response(
+        ctx,
+        serenity::CreateInteractionResponse::UpdateMessage(
+            serenity::CreateInteractionResponseMessage::new()
+                .components(vec![build_upvote_button(quote_id, votes)])
+        )
     ).await?;
 
-    // Handle button interactions
-    while let Some(press) = serenity::collector::ComponentInteractionCollector::new(ctx)
-        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-        .timeout(std::time::Duration::from_secs(1800))
-        .await {
-            if press.data.custom_id == prev_id {
-                page_num = page_num.checked_sub(1).unwrap_or(pages.len() - 1)
-            } else if press.data.custom_id == next_id {
-                page_num += 1;
-                if page_num >= pages.len() { page_num = 0; }
-            } else {
-                continue;
-            }
+    Ok(())
+}
 
-            press.create_response(
-                ctx.serenity_context(),
-                serenity::CreateInteractionResponse::UpdateMessage(
-                    serenity::CreateInteractionResponseMessage::new()
-                        .embed(serenity::CreateEmbed::new()
-                            .description(&pages[page_num])
-                            .colour(0x0b4a6f)
-                            .title("Quotes")
-                        )
-                )
-            ).await?;
+//--------------------------
+// Quote of the day
+//--------------------------
+pub async fn quote_of_the_day(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let current_time = Utc::now();
+
+    for guild in data.database.get_qotd_guilds().await? {
+        let local_time = current_time.with_timezone(&guild.tz());
+        let today = local_time.date_naive();
+
+        // Posted at the first check on or after the guild's hour, so downtime only delays it
+        if local_time.hour() < guild.qotd_hour as u32 || guild.qotd_last_posted.is_some_and(|d| d >= today) {
+            continue;
+        }
+
+        // One guild failing shouldn't stop the rest from getting their quote
+        if let Err(e) = post_quote_of_the_day(ctx, data, &guild, today).await {
+            error!("[ QUOTES ] An error occurred posting the quote of the day - Guild ID: {} - {e:?}", guild.guild_id);
+        }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn post_quote_of_the_day(ctx: &serenity::Context, data: &Data, guild: &QotdGuild, today: NaiveDate) -> Result<(), Error> {
+    match data.database.pick_quote_of_the_day(guild.guild_id).await? {
+        Some(quote) => {
+            let quote_id = quote.quote_id;
+            let votes = data.database.count_quote_votes(guild.guild_id, quote_id).await?;
+            let upvote_button = build_upvote_button(quote_id, votes);
+            let quote_embed = build_single_quote_embed(&ctx.http, &data.database, quote).await?;
+
+            let msg = serenity::CreateMessage::new()
+                .content(":speech_balloon: **Quote of the Day**")
+                .embed(quote_embed)
+                .components(vec![upvote_button]);
+
+            serenity::ChannelId::new(guild.qotd_channel).send_message(ctx, msg).await?;
+
+            // Only counts as featured once someone could actually see it
+            data.database.set_quote_featured(guild.guild_id, quote_id, Utc::now().timestamp()).await?;
+        },
+        None => info!("[ QUOTES ] No quotes to post for the quote of the day - Guild ID: {}", guild.guild_id)
+    }
+
+    data.database.set_qotd_last_posted(guild.guild_id, today).await?;
+
+    Ok(())
+}
+
+//--------------------
+// Tests
+//--------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_terms_keep_quoted_phrases_together() {
+        assert_eq!(parse_search_terms("pizza  party"), vec!["pizza", "party"]);
+        assert_eq!(parse_search_terms(r#"said "no way" twice"#), vec!["said", "no way", "twice"]);
+        assert_eq!(parse_search_terms(r#"" " lonely"#), vec!["lonely"]);
+        assert!(parse_search_terms("   ").is_empty());
+    }
+
+    #[test]
+    fn parse_tags_normalises_and_dedupes() {
+        assert_eq!(parse_tags(" Funny, work ,,FUNNY").unwrap(), vec!["funny", "work"]);
+    }
+
+    #[test]
+    fn parse_tags_rejects_bad_lists() {
+        assert!(parse_tags(" , ").is_err());
+        assert!(parse_tags(&"a".repeat(33)).is_err());
+        assert!(parse_tags(&(0..11).map(|i| i.to_string()).collect::<Vec<_>>().join(",")).is_err());
+    }
+}
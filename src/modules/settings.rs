@@ -1,4 +1,5 @@
 use crate::{Context, Error};
+use chrono_tz::{Tz, TZ_VARIANTS};
 
 #[derive(poise::ChoiceParameter)]
 enum EnableDisable {
@@ -19,7 +20,7 @@ impl std::fmt::Display for EnableDisable {
     slash_command,
     guild_only,
     member_cooldown = 5,
-    subcommands("command_ping")
+    subcommands("command_ping", "timezone")
 )]
 pub async fn settings(_: Context<'_>) -> Result<(), Error> {
     Ok(())
@@ -43,10 +44,7 @@ pub async fn command_ping(
         EnableDisable::Disable => false  
     };
     
-    sqlx::query!("UPDATE user_settings SET command_ping = ? WHERE guild_id = ? AND user_id = ?", bool_choice, guild_id, user_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_command_ping(guild_id, user_id, bool_choice).await?;
         
     let msg = match choice {
           EnableDisable::Enable => "You have **enabled** pings for certain commands!",
@@ -56,4 +54,42 @@ pub async fn command_ping(
     ctx.say(msg).await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Set the timezone used for birthdays, monthly resets and quote dates
+#[poise::command(
+    slash_command,
+    required_permissions = "MANAGE_GUILD"
+)]
+pub async fn timezone(
+    ctx: Context<'_>,
+    #[description = "A timezone name such as Europe/London or Australia/Sydney."]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: String
+) -> Result<(), Error> {
+    let timezone: Tz = match timezone.parse() {
+        Ok(tz) => tz,
+        Err(_) => return Err(format!("`{timezone}` is not a valid timezone! Pick one from the list.").into())
+    };
+
+    ctx.data().database.set_timezone(ctx.guild_id().unwrap().get(), timezone).await?;
+
+    ctx.say(format!("The server's timezone is now **{}**!", timezone.name())).await?;
+
+    Ok(())
+}
+
+//--------------------
+// Function library
+//--------------------
+async fn autocomplete_timezone<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = &'static str> + 'a {
+    let partial = partial.to_lowercase();
+
+    TZ_VARIANTS.iter()
+        .map(|tz| tz.name())
+        .filter(move |name| name.to_lowercase().contains(&partial))
+        .take(25) // Discord's limit for autocomplete choices
+}
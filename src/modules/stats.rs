@@ -121,30 +121,19 @@ pub async fn stats(
     
     let user_id = user.id;
     let guild_id = ctx.guild_id().unwrap().get();
-    user_table_check(ctx, &user).await;
+    user_table_check(ctx, &user).await?;
 
     // Update Voice Time
     let vc_info = ctx.guild().unwrap().voice_states.clone();
-    let vc_info = vc_info.get(&user_id);
-
-    if vc_info.is_some() {
-        match crate::vctracker::recheck_time(vc_info.unwrap(), &ctx.data().database).await {
-            Ok(_) => {},
-            Err(e) => return Err(e)
-        }
+    if let Some(vc_info) = vc_info.get(&user_id) {
+        crate::vctracker::recheck_time(vc_info, &ctx.data().database).await?;
     }
 
     // Grab quote counts
-    let quote_data = sqlx::query!("SELECT CAST(SUM(IF(adder_id = ?, 1, 0)) AS INTEGER) AS quotes_added, CAST(SUM(IF(sayer_id = ?, 1, 0)) AS INTEGER) AS times_quoted FROM quotes WHERE guild_id = ?", user_id.get(), user_id.get(), guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let (quotes_added, times_quoted) = ctx.data().database.get_quote_counts(guild_id, user_id.get()).await?;
     
     // Build stats embeds
-    let user_data = sqlx::query_as!(User, "SELECT * FROM users WHERE guild_id = ? AND user_id = ?", guild_id, user_id.get())
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let user_data: User = ctx.data().database.get_user(guild_id, user_id.get()).await?;
 
     let vctime = format!("{}h {}m {}s",
         (user_data.vctrack_total_time / 60) / 60,
@@ -154,7 +143,7 @@ pub async fn stats(
     
     let avatar_url = user.avatar_url().unwrap_or(String::new());
     let stat_embeds: [serenity::CreateEmbed; 3] = [
-        build_general_embed(&user_data, &avatar_url, vctime, quote_data.quotes_added.unwrap(), quote_data.times_quoted.unwrap()),
+        build_general_embed(&user_data, &avatar_url, vctime, quotes_added, times_quoted),
         build_misc_embed(&user_data, &avatar_url),
         build_minigames_embed(&user_data, &avatar_url)
     ];
@@ -216,10 +205,7 @@ pub async fn serverstats(ctx: Context<'_>) -> Result<(), Error> {
     // `users` table data
     let guild_id = ctx.guild_id().unwrap().get();
 
-    let server_data = sqlx::query!("SELECT * FROM users WHERE guild_id = ?", guild_id)
-        .fetch_all(&ctx.data().database)
-        .await
-        .unwrap();
+    let server_data = ctx.data().database.get_guild_users(guild_id).await?;
 
     // If no data, return msg.
     if server_data.is_empty() {
@@ -228,11 +214,7 @@ pub async fn serverstats(ctx: Context<'_>) -> Result<(), Error> {
     }
 
     // `quotes` table data
-    let quote_count = sqlx::query!("SELECT COUNT(quote_id) AS count FROM quotes WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap()
-        .count;
+    let quote_count = ctx.data().database.count_quotes(guild_id).await?;
 
     // Server stats struct construction
     let mut server_stats = data::ServerStats::default();
@@ -1,4 +1,6 @@
 use crate::{Context, Error};
+use crate::storage::Storage;
+use chrono::Utc;
 use poise::serenity_prelude as serenity;
 use tracing::{info, warn};
 
@@ -19,15 +21,9 @@ pub async fn ignorechannel(
     ctx: Context<'_>,
     #[description = "The VC channel to ignore for tracking time."] channel: Option<serenity::Channel>,
 ) -> Result<(), Error> {
-    let query_channel = match &channel {
-        Some(c) => c.id().get().to_string(),
-        None => String::from("NULL"),
-    };
+    let channel_id = channel.as_ref().map(|c| c.id().get());
 
-    sqlx::query!("UPDATE guild_settings SET vctrack_ignored_channel = ? WHERE guild_id = ?", query_channel, ctx.guild_id().unwrap().get())
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_vctrack_ignored_channel(ctx.guild_id().unwrap().get(), channel_id).await?;
 
     match &channel {
         Some(c) => {
@@ -65,23 +61,13 @@ pub async fn vctop(
     futures::future::join_all(futures).await;
 
     // Grab information
-    let vctop_all = sqlx::query!("SELECT display_name, vctrack_total_time FROM users WHERE guild_id = ? ORDER BY vctrack_total_time DESC LIMIT 10", guild_id)
-        .fetch_all(&ctx.data().database);
-    let vctop_monthly = sqlx::query!("SELECT display_name, vctrack_monthly_time FROM users WHERE guild_id = ? ORDER BY vctrack_monthly_time DESC LIMIT 10", guild_id)
-        .fetch_all(&ctx.data().database);
+    let vctop_all = ctx.data().database.get_vc_leaderboard(guild_id, false);
+    let vctop_monthly = ctx.data().database.get_vc_leaderboard(guild_id, true);
         
     let futures_data = futures::future::join(vctop_all, vctop_monthly).await;
     
-    let vctop_all: Vec<(String, u32)> = futures_data.0
-        .unwrap()
-        .iter()
-        .map(|r| (r.display_name.to_string(), r.vctrack_total_time))
-        .collect();
-    let vctop_monthly: Vec<(String, u32)> = futures_data.1
-        .unwrap()
-        .iter()
-        .map(|r| (r.display_name.to_string(), r.vctrack_monthly_time))
-        .collect();
+    let vctop_all = futures_data.0?;
+    let vctop_monthly = futures_data.1?;
 
     // Build embeds and do interaction
     let vctop_pages: Vec<serenity::CreateEmbed> = vec![
@@ -133,7 +119,7 @@ pub async fn vctop(
 // Recheck VC participant time
 pub async fn recheck_time(
     voice_state: &serenity::VoiceState,
-    database: &sqlx::MySqlPool
+    database: &Storage
 ) -> Result<(), Error> {
     // Grab necessary info
     let user_id = voice_state.user_id.get();
@@ -144,54 +130,46 @@ pub async fn recheck_time(
     };
 
     let channel_id = voice_state.channel_id.unwrap().get();
-    let ignored_channel = sqlx::query!("SELECT vctrack_ignored_channel FROM guild_settings WHERE guild_id = ?", guild_id)
-        .fetch_one(database)
-        .await
-        .unwrap()
-        .vctrack_ignored_channel
-        .unwrap_or(0);
+    let ignored_channel = database.get_vctrack_ignored_channel(guild_id).await?.unwrap_or(0);
 
     // If channel_id isn't ignored, update times
     if channel_id != ignored_channel {
         // Skip if join time is 0
-        let join_time = sqlx::query!("SELECT vctrack_join_time FROM users WHERE guild_id = ? AND user_id = ?", guild_id, user_id)
-            .fetch_one(database)
-            .await
-            .unwrap()
-            .vctrack_join_time;
+        let join_time = database.get_vc_join_time(guild_id, user_id).await?;
 
         if join_time == 0 {
             warn!("[ VCTRACKER ] SAFEGUARD - Skipping user's time update. Guild ID: {guild_id} - User ID: {user_id}");
         } else {
             // Update users time
-            let query = format!("
-                UPDATE users SET vctrack_total_time = (UNIX_TIMESTAMP() - vctrack_join_time) + vctrack_total_time WHERE guild_id = {guild_id} AND user_id = {user_id};
-                UPDATE users SET vctrack_monthly_time = vctrack_monthly_time + (UNIX_TIMESTAMP() - vctrack_join_time) WHERE guild_id = {guild_id} AND user_id = {user_id};
-                UPDATE users SET vctrack_join_time = UNIX_TIMESTAMP() WHERE guild_id = {guild_id} AND user_id = {user_id}
-            ");
-
-            sqlx::raw_sql(&query)
-                .execute(database)
-                .await
-                .unwrap();
+            let now = Utc::now().timestamp();
+            database.add_vc_time(guild_id, user_id, now, now).await?;
         }
     }
 
     Ok(())
 }
 
-// Reset monthly times
-pub async fn vctracker_reset_monthly(database: &sqlx::MySqlPool) {
-    let current_time = chrono::Utc::now();
+// Reset monthly times for every guild that has started a new month in its own timezone
+pub async fn vctracker_reset_monthly(database: &Storage) -> Result<(), Error> {
+    let current_time = Utc::now();
+
+    for guild in database.get_vc_reset_guilds().await? {
+        let current_month = current_time.with_timezone(&guild.tz()).format("%Y-%m").to_string();
 
-    if current_time.format("%d %H:%M:%S").to_string() == "01 00:00:00" { // Day Hour:Minute:Second
-        sqlx::query!("UPDATE users SET vctrack_monthly_time = 0")
-            .execute(database)
-            .await
-            .unwrap();
+        match guild.vctrack_reset_month {
+            Some(month) if month == current_month => continue,
+            Some(_) => {
+                database.reset_monthly_vc_time(guild.guild_id).await?;
 
-        info!("[ VCTRACKER ] Reset monthly VC times for every user.");
+                info!("[ VCTRACKER ] Reset monthly VC times - Guild ID: {}", guild.guild_id);
+            },
+            None => {} // Guild hasn't been tracked yet, start counting from this month
+        }
+
+        database.set_vc_reset_month(guild.guild_id, &current_month).await?;
     }
+
+    Ok(())
 }
 
 // Build vctop embeds
@@ -25,20 +25,14 @@ pub async fn setmessage(
 
     // Handle empty channel (disables messaging)
     if message.is_none() {
-        sqlx::query!("UPDATE welcome SET message = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_welcome_message(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer including a custom message in the welcome messages!", ctx.author())).await?;
         return Ok(());
     }
 
     // Grab channel ID and query DB
-    sqlx::query!("UPDATE welcome SET message = ? WHERE guild_id = ?", message.as_ref().unwrap(), guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_welcome_message(guild_id, message.as_deref()).await?;
 
     ctx.say(format!("{}, set the custom welcome message to: ```{}```", ctx.author(), message.unwrap())).await?;
     Ok(())
@@ -58,20 +52,14 @@ pub async fn setimage(
 
     // Handle empty channel (disables messaging)
     if image_url.is_none() {
-        sqlx::query!("UPDATE welcome SET image_url = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_welcome_image(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer including an image in the welcome messages!", ctx.author())).await?;
         return Ok(());
     }
 
     // Query DB
-    sqlx::query!("UPDATE welcome SET image_url = ? WHERE guild_id = ?", image_url.as_ref().unwrap(), guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_welcome_image(guild_id, image_url.as_deref()).await?;
 
     ctx.say(format!("{}, image for the welcome messages set to {}", ctx.author(), image_url.unwrap())).await?;
     Ok(())
@@ -90,10 +78,7 @@ pub async fn setchannel(
 
     // Handle empty channel (disables messaging)
     if channel.is_none() {
-        sqlx::query!("UPDATE welcome SET channel_id = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_welcome_channel(guild_id, None).await?;
 
         ctx.say(format!("{}, no longer sending welcome messages!", ctx.author())).await?;
         return Ok(());
@@ -102,10 +87,7 @@ pub async fn setchannel(
     // Grab channel ID and query DB
     let channel_id = channel.as_ref().unwrap().id().get();
 
-    sqlx::query!("UPDATE welcome SET channel_id = ? WHERE guild_id = ?", channel_id, guild_id)
-        .execute(&ctx.data().database)
-        .await
-        .unwrap();
+    ctx.data().database.set_welcome_channel(guild_id, Some(channel_id)).await?;
 
     ctx.say(format!("{}, now sending welcome messages to {}!", ctx.author(), channel.unwrap())).await?;
     Ok(())
@@ -124,20 +106,14 @@ pub async fn setleavechannel(
     let guild_id = ctx.guild_id().unwrap().get();
 
     // Set the channel to send leave announcements to
-    if channel.is_some() {
-        let channel_id = channel.as_ref().unwrap().id().get();
+    if let Some(channel) = channel {
+        let channel_id = channel.id().get();
 
-        sqlx::query!("UPDATE guild_settings SET member_leave_channel_id = ? WHERE guild_id = ?", channel_id, guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_member_leave_channel(guild_id, Some(channel_id)).await?;
 
-        ctx.say(format!("Now sending a message when a server member leaves to {}!", channel.unwrap())).await?;
+        ctx.say(format!("Now sending a message when a server member leaves to {}!", channel)).await?;
     } else {
-        sqlx::query!("UPDATE guild_settings SET member_leave_channel_id = NULL WHERE guild_id = ?", guild_id)
-            .execute(&ctx.data().database)
-            .await
-            .unwrap();
+        ctx.data().database.set_member_leave_channel(guild_id, None).await?;
 
         ctx.say("No longer announcing when a server member leaves!").await?;
     }
@@ -152,12 +128,9 @@ pub async fn setleavechannel(
 async fn welcome_channel_check(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().unwrap().get();
 
-    let channel = sqlx::query!("SELECT channel_id FROM welcome WHERE guild_id = ?", guild_id)
-        .fetch_one(&ctx.data().database)
-        .await
-        .unwrap();
+    let settings = ctx.data().database.get_welcome_settings(guild_id).await?;
 
-    match channel.channel_id {
+    match settings.channel_id {
         Some(_) => Ok(true),
         None => {
             ctx.send(poise::CreateReply::default()
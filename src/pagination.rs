@@ -0,0 +1,110 @@
+use crate::{Context, Error};
+use poise::serenity_prelude as serenity;
+
+//--------------------------
+// Embed pagination
+//--------------------------
+// Send pages of text as an embed with Previous/Next buttons that wrap around.
+// Button ids are prefixed with the command's id so several lists can be open at once. They stop responding after 30 minutes.
+pub async fn paginate(ctx: Context<'_>, title: &str, colour: u32, pages: &[String]) -> Result<(), Error> {
+    let build_embed = |page_num: usize| {
+        let mut embed = serenity::CreateEmbed::new()
+            .description(&pages[page_num])
+            .colour(colour)
+            .title(title);
+
+        if pages.len() > 1 {
+            embed = embed.footer(serenity::CreateEmbedFooter::new(format!("Page {}/{}", page_num + 1, pages.len())));
+        }
+
+        embed
+    };
+
+    // Nothing to flip through
+    if pages.len() <= 1 {
+        ctx.send(poise::CreateReply::default().embed(build_embed(0))).await?;
+        return Ok(());
+    }
+
+    // Create initial embed
+    let mut page_num = 0;
+    let ctx_id = ctx.id();
+    let prev_id = format!("{ctx_id}prev");
+    let next_id = format!("{ctx_id}next");
+
+    let buttons: Vec<serenity::CreateButton> = vec![
+        serenity::CreateButton::new(&prev_id).label("Previous"),
+        serenity::CreateButton::new(&next_id).label("Next")
+    ];
+    let buttons = serenity::CreateActionRow::Buttons(buttons);
+
+    ctx.send(poise::CreateReply::default()
+        .embed(build_embed(page_num))
+        .components(vec![buttons])
+    ).await?;
+
+    // Handle button interactions
+    while let Some(press) = serenity::collector::ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(1800))
+        .await {
+            if press.data.custom_id == prev_id {
+                page_num = page_num.checked_sub(1).unwrap_or(pages.len() - 1)
+            } else if press.data.custom_id == next_id {
+                page_num += 1;
+                if page_num >= pages.len() { page_num = 0; }
+            } else {
+                continue;
+            }
+
+            press.create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(build_embed(page_num))
+                )
+            ).await?;
+    }
+
+    Ok(())
+}
+
+//--------------------------
+// Page splitting
+//--------------------------
+// Join one formatted line per item into pages of `per_page` items, ready for paginate().
+// The formatter gets the item's position in the whole list, starting at 0.
+pub fn split_into_pages<T>(items: &[T], per_page: usize, format_item: impl Fn(usize, &T) -> String) -> Vec<String> {
+    items.chunks(per_page)
+        .enumerate()
+        .map(|(page_num, page)| {
+            page.iter()
+                .enumerate()
+                .map(|(i, item)| format_item(page_num * per_page + i, item))
+                .collect()
+        })
+        .collect()
+}
+
+//--------------------------
+// Tests
+//--------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_into_pages_numbers_items_across_pages() {
+        let items: Vec<u32> = (1..=7).collect();
+        let pages = split_into_pages(&items, 3, |i, item| format!("{i}:{item} "));
+
+        assert_eq!(pages, vec!["0:1 1:2 2:3 ", "3:4 4:5 5:6 ", "6:7 "]);
+    }
+
+    #[test]
+    fn split_into_pages_has_no_pages_for_no_items() {
+        let items: Vec<u32> = Vec::new();
+
+        assert!(split_into_pages(&items, 5, |_, item| item.to_string()).is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Data, Error};
+use crate::modules::{birthday, quotes, vctracker};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use futures::future::BoxFuture;
+use poise::serenity_prelude as serenity;
+use std::str::FromStr;
+use std::sync::{Arc, Once};
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+static SCHEDULER: Once = Once::new();
+
+// Failed runs are retried after this long, doubling with each failure in a row up to RETRY_MAX_DELAY
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(3600);
+
+//--------------------------
+// Job registry
+//--------------------------
+pub type JobFn = for<'a> fn(&'a serenity::Context, &'a Data) -> BoxFuture<'a, Result<(), Error>>;
+
+pub struct Job {
+    pub name: &'static str,
+    pub schedule: &'static str, // Cron expression in UTC: sec min hour day month weekday year
+    pub run: JobFn,
+}
+
+pub const JOBS: &[Job] = &[
+    Job {
+        name: "birthday_check",
+        schedule: "0 0 * * * * *", // Hourly, each guild is checked against its own timezone
+        run: |ctx, data| Box::pin(birthday::birthday_check(ctx, data)),
+    },
+    Job {
+        name: "birthday_role_expiry",
+        schedule: "0 */15 * * * * *", // Every 15 minutes. Every UTC offset is a whole quarter hour, so each local midnight lines up with a run.
+        run: |ctx, data| Box::pin(birthday::expire_birthday_roles(ctx, data)),
+    },
+    Job {
+        name: "quote_of_the_day",
+        schedule: "0 0 * * * * *", // Hourly, each guild posts at its own hour in its timezone
+        run: |ctx, data| Box::pin(quotes::quote_of_the_day(ctx, data)),
+    },
+    Job {
+        name: "vctracker_reset_monthly",
+        schedule: "0 0 * * * * *", // Hourly, guilds are reset once the 1st starts in their timezone
+        run: |_, data| Box::pin(vctracker::vctracker_reset_monthly(&data.database)),
+    },
+];
+
+impl Job {
+    pub fn cron(&self) -> Schedule {
+        Schedule::from_str(self.schedule).expect("invalid cron expression for scheduled job")
+    }
+
+    // Run the job now and record it if it succeeded
+    pub async fn trigger(&self, ctx: &serenity::Context, data: &Data, run_time: DateTime<Utc>) -> Result<(), Error> {
+        (self.run)(ctx, data).await?;
+        data.database.set_job_last_run(self.name, run_time.timestamp()).await?;
+
+        Ok(())
+    }
+}
+
+pub fn find_job(name: &str) -> Option<&'static Job> {
+    JOBS.iter().find(|job| job.name == name)
+}
+
+//--------------------------
+// Job runner
+//--------------------------
+// Spawns a loop per job. Safe to call on every Ready event, the loops are only started once.
+pub fn start(ctx: &serenity::Context, data: &Data) {
+    SCHEDULER.call_once(|| {
+        let ctx = Arc::new(ctx.clone());
+        let data = Arc::new(data.clone());
+
+        for job in JOBS {
+            let ctx = Arc::clone(&ctx);
+            let data = Arc::clone(&data);
+
+            tokio::spawn(async move {
+                run_job_loop(job, &ctx, &data).await;
+            });
+        }
+    });
+}
+
+async fn run_job_loop(job: &'static Job, ctx: &serenity::Context, data: &Data) {
+    let schedule = job.cron();
+
+    // Start from the last recorded run so ticks missed while the bot was down get caught up.
+    // Jobs that have never run start counting from now instead of firing straight away.
+    let mut cursor = match data.database.get_job_last_run(job.name).await {
+        Ok(Some(last_run)) => DateTime::from_timestamp(last_run, 0).unwrap_or_else(Utc::now),
+        Ok(None) => {
+            let now = Utc::now();
+            if let Err(e) = data.database.set_job_last_run(job.name, now.timestamp()).await {
+                error!("[ SCHEDULER ] Unable to register job {}: {e:?}", job.name);
+            }
+            now
+        },
+        Err(e) => {
+            error!("[ SCHEDULER ] Unable to fetch the last run of job {}, not catching up missed runs: {e:?}", job.name);
+            Utc::now()
+        }
+    };
+
+    let mut failures: u32 = 0;
+
+    loop {
+        let Some(next_run) = schedule.after(&cursor).next() else {
+            warn!("[ SCHEDULER ] Job {} has no upcoming runs, stopping it", job.name);
+            return;
+        };
+
+        let now = Utc::now();
+        if next_run > now {
+            let duration = next_run.signed_duration_since(now);
+            info!("[ SCHEDULER ] Seconds until next run of {}: {}", job.name, duration.num_seconds());
+
+            tokio::time::sleep(duration.to_std().unwrap_or_default()).await;
+        } else {
+            // Any number of missed ticks collapse into this single run
+            info!("[ SCHEDULER ] Catching up on job {} missed at {next_run}", job.name);
+        }
+
+        // Covers every tick up to now so a slow run or catch-up doesn't fire twice
+        let run_time = next_run.max(Utc::now());
+
+        match job.trigger(ctx, data, run_time).await {
+            Ok(_) => {
+                info!("[ SCHEDULER ] Finished job {}", job.name);
+                cursor = run_time;
+                failures = 0;
+            },
+            Err(e) => {
+                // The cursor stays put, so the missed tick is run again once the delay is up
+                let delay = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(failures)).min(RETRY_MAX_DELAY);
+                failures += 1;
+
+                error!("[ SCHEDULER ] An error occurred running job {}, retrying in {}s: {e:?}", job.name, delay.as_secs());
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
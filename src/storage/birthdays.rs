@@ -0,0 +1,327 @@
+use super::{on_pool, Storage};
+use crate::birthday::Birthday;
+use chrono::NaiveDate;
+
+//--------------------------
+// Row structures
+//--------------------------
+// A birthday along with the member's stored display name, so lists don't have to look anyone up
+#[derive(sqlx::FromRow, Debug)]
+pub struct ListedBirthday {
+    #[sqlx(flatten)]
+    pub birthday: Birthday,
+    pub display_name: Option<String>,
+}
+
+// A member who wants a DM `days` before target_user_id's birthday
+#[derive(sqlx::FromRow, Debug)]
+pub struct BirthdayReminder {
+    pub user_id: u64,
+    pub target_user_id: u64,
+    pub days: u8,
+}
+
+// A birthday role given to a member that still has to be taken away
+#[derive(sqlx::FromRow, Debug)]
+pub struct BirthdayRoleGrant {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub role_id: u64,
+}
+
+//--------------------------
+// birthday table
+//--------------------------
+impl Storage {
+    pub async fn get_birthday(&self, guild_id: u64, user_id: u64) -> Result<Option<Birthday>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM birthday WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    pub async fn insert_birthday(&self, birthday: &Birthday) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("INSERT INTO birthday (guild_id, user_id, birthday, birthmonth, birthyear, nickname, private, show_age) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+                .bind(birthday.guild_id as i64)
+                .bind(birthday.user_id as i64)
+                .bind(birthday.birthday)
+                .bind(birthday.birthmonth)
+                .bind(birthday.birthyear)
+                .bind(&birthday.nickname)
+                .bind(birthday.private)
+                .bind(birthday.show_age)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn update_birthday(&self, birthday: &Birthday) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE birthday SET birthmonth = ?, birthday = ?, birthyear = ?, nickname = ?, private = ?, show_age = ? WHERE guild_id = ? AND user_id = ?")
+                .bind(birthday.birthmonth)
+                .bind(birthday.birthday)
+                .bind(birthday.birthyear)
+                .bind(&birthday.nickname)
+                .bind(birthday.private)
+                .bind(birthday.show_age)
+                .bind(birthday.guild_id as i64)
+                .bind(birthday.user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn delete_birthday(&self, guild_id: u64, user_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("DELETE FROM birthday WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Same as list_birthdays, with display names from the users table
+    pub async fn list_birthdays_with_names(&self, guild_id: u64, month: Option<u8>) -> Result<Vec<ListedBirthday>, sqlx::Error> {
+        let month_filter = if month.is_some() { "AND b.birthmonth = ?" } else { "" };
+        let query = format!("SELECT b.*, u.display_name FROM birthday b \
+            LEFT JOIN users u ON u.guild_id = b.guild_id AND u.user_id = b.user_id \
+            WHERE b.guild_id = ? {month_filter} ORDER BY b.birthmonth, b.birthday");
+
+        on_pool!(self, pool => {
+            let mut query = sqlx::query_as(&query).bind(guild_id as i64);
+            if let Some(month) = month {
+                query = query.bind(month);
+            }
+
+            query.fetch_all(pool).await
+        })
+    }
+
+    // Insert a batch of birthdays in one transaction. Existing birthdays are skipped, or have their date replaced if
+    // `overwrite` is set, along with their name when the row has one. Returns how many were added and how many were updated.
+    pub async fn import_birthdays(&self, birthdays: &[Birthday], overwrite: bool) -> Result<(u32, u32), sqlx::Error> {
+        let query = format!("{} birthday (guild_id, user_id, birthday, birthmonth, nickname) VALUES (?, ?, ?, ?, ?)", self.insert_ignore());
+
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+            let (mut added, mut updated) = (0, 0);
+
+            for birthday in birthdays {
+                let inserted = sqlx::query(&query)
+                    .bind(birthday.guild_id as i64)
+                    .bind(birthday.user_id as i64)
+                    .bind(birthday.birthday)
+                    .bind(birthday.birthmonth)
+                    .bind(&birthday.nickname)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+
+                if inserted > 0 {
+                    added += 1;
+                } else if overwrite {
+                    sqlx::query("UPDATE birthday SET birthmonth = ?, birthday = ?, nickname = COALESCE(?, nickname) WHERE guild_id = ? AND user_id = ?")
+                        .bind(birthday.birthmonth)
+                        .bind(birthday.birthday)
+                        .bind(&birthday.nickname)
+                        .bind(birthday.guild_id as i64)
+                        .bind(birthday.user_id as i64)
+                        .execute(&mut *tx)
+                        .await?;
+                    updated += 1;
+                }
+            }
+
+            tx.commit().await?;
+            Ok((added, updated))
+        })
+    }
+
+    // All birthdays in a guild sorted by date, optionally only for one month
+    pub async fn list_birthdays(&self, guild_id: u64, month: Option<u8>) -> Result<Vec<Birthday>, sqlx::Error> {
+        on_pool!(self, pool => {
+            match month {
+                Some(month) => {
+                    sqlx::query_as("SELECT * FROM birthday WHERE guild_id = ? AND birthmonth = ? ORDER BY birthmonth, birthday")
+                        .bind(guild_id as i64)
+                        .bind(month)
+                        .fetch_all(pool)
+                        .await
+                },
+                None => {
+                    sqlx::query_as("SELECT * FROM birthday WHERE guild_id = ? ORDER BY birthmonth, birthday")
+                        .bind(guild_id as i64)
+                        .fetch_all(pool)
+                        .await
+                }
+            }
+        })
+    }
+}
+
+//--------------------------
+// birthday_role_grants table
+//--------------------------
+impl Storage {
+    // Record a granted birthday role, moving the expiry if it was already granted
+    pub async fn add_birthday_role_grant(&self, guild_id: u64, user_id: u64, role_id: u64, expires_at: i64) -> Result<(), sqlx::Error> {
+        let query = match self {
+            Storage::MySql(_) => "INSERT INTO birthday_role_grants (guild_id, user_id, role_id, expires_at) VALUES (?, ?, ?, ?) \
+                ON DUPLICATE KEY UPDATE expires_at = VALUES(expires_at)",
+            Storage::Sqlite(_) => "INSERT INTO birthday_role_grants (guild_id, user_id, role_id, expires_at) VALUES (?, ?, ?, ?) \
+                ON CONFLICT (guild_id, user_id, role_id) DO UPDATE SET expires_at = excluded.expires_at",
+        };
+
+        on_pool!(self, pool => {
+            sqlx::query(query)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(role_id as i64)
+                .bind(expires_at)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Every grant that expired at or before the given unix timestamp
+    pub async fn get_expired_birthday_role_grants(&self, now: i64) -> Result<Vec<BirthdayRoleGrant>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT guild_id, user_id, role_id FROM birthday_role_grants WHERE expires_at <= ?")
+                .bind(now)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn delete_birthday_role_grant(&self, grant: &BirthdayRoleGrant) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("DELETE FROM birthday_role_grants WHERE guild_id = ? AND user_id = ? AND role_id = ?")
+                .bind(grant.guild_id as i64)
+                .bind(grant.user_id as i64)
+                .bind(grant.role_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+}
+
+//--------------------------
+// birthday_announcements table
+//--------------------------
+impl Storage {
+    // Members whose birthday on the given date was already announced, for dates after the guild's last announced date
+    pub async fn get_announced_birthdays(&self, guild_id: u64, date: NaiveDate) -> Result<Vec<u64>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT user_id FROM birthday_announcements WHERE guild_id = ? AND birthday_date = ?")
+                .bind(guild_id as i64)
+                .bind(date)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn add_birthday_announcements(&self, guild_id: u64, user_ids: &[u64], date: NaiveDate) -> Result<(), sqlx::Error> {
+        let query = format!("{} birthday_announcements (guild_id, user_id, birthday_date) VALUES (?, ?, ?)", self.insert_ignore());
+
+        on_pool!(self, pool => {
+            for user_id in user_ids {
+                sqlx::query(&query)
+                    .bind(guild_id as i64)
+                    .bind(*user_id as i64)
+                    .bind(date)
+                    .execute(pool)
+                    .await?;
+            }
+        });
+
+        Ok(())
+    }
+
+    // Once the guild's last announced date moves past them the rows aren't needed anymore
+    pub async fn clear_birthday_announcements(&self, guild_id: u64, through: NaiveDate) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("DELETE FROM birthday_announcements WHERE guild_id = ? AND birthday_date <= ?")
+                .bind(guild_id as i64)
+                .bind(through)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+}
+
+//--------------------------
+// birthday_reminders table
+//--------------------------
+impl Storage {
+    // Returns false if the user was already on the list
+    pub async fn add_birthday_reminder(&self, guild_id: u64, user_id: u64, target_user_id: u64) -> Result<bool, sqlx::Error> {
+        let query = format!("{} birthday_reminders (guild_id, user_id, target_user_id) VALUES (?, ?, ?)", self.insert_ignore());
+
+        let added = on_pool!(self, pool => {
+            sqlx::query(&query)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(target_user_id as i64)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(added > 0)
+    }
+
+    // Returns false if the user wasn't on the list
+    pub async fn delete_birthday_reminder(&self, guild_id: u64, user_id: u64, target_user_id: u64) -> Result<bool, sqlx::Error> {
+        let deleted = on_pool!(self, pool => {
+            sqlx::query("DELETE FROM birthday_reminders WHERE guild_id = ? AND user_id = ? AND target_user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(target_user_id as i64)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(deleted > 0)
+    }
+
+    // Everyone the user asked to be reminded about
+    pub async fn get_birthday_reminder_targets(&self, guild_id: u64, user_id: u64) -> Result<Vec<u64>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT target_user_id FROM birthday_reminders WHERE guild_id = ? AND user_id = ? ORDER BY target_user_id")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Every reminder in the guild from users who have reminders turned on
+    pub async fn get_guild_birthday_reminders(&self, guild_id: u64) -> Result<Vec<BirthdayReminder>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT r.user_id, r.target_user_id, s.birthday_reminder_days AS days FROM birthday_reminders r \
+                JOIN user_settings s ON s.guild_id = r.guild_id AND s.user_id = r.user_id \
+                WHERE r.guild_id = ? AND s.birthday_reminder_days IS NOT NULL")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use super::{on_pool, Storage};
+use crate::customgifs::CustomGif;
+
+//--------------------------
+// bot_settings table
+//--------------------------
+#[derive(sqlx::FromRow, Debug)]
+pub struct BotSettings {
+    pub glados_gif: String,
+    pub roulette_click_gif: String,
+    pub roulette_fire_gif: String,
+}
+
+//--------------------------
+// custom_gifs / bot_settings tables
+//--------------------------
+impl Storage {
+    pub async fn get_custom_gifs(&self, guild_id: u64, gif_type: &str) -> Result<Vec<CustomGif>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM custom_gifs WHERE guild_id = ? AND gif_type = ? ORDER BY gif_id")
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn get_random_custom_gif(&self, guild_id: u64, gif_type: &str) -> Result<Option<CustomGif>, sqlx::Error> {
+        let query = format!("SELECT * FROM custom_gifs WHERE guild_id = ? AND gif_type = ? ORDER BY {} LIMIT 1", self.random_fn());
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    // Adds the GIF after the current highest ID for its type and returns the new ID
+    pub async fn add_custom_gif(&self, guild_id: u64, gif_type: &str, gif_name: &str, gif_url: &str) -> Result<u32, sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            let max_id: Option<u32> = sqlx::query_scalar("SELECT MAX(gif_id) FROM custom_gifs WHERE guild_id = ? AND gif_type = ?")
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .fetch_one(&mut *tx)
+                .await?;
+            let gif_id = max_id.unwrap_or(0) + 1;
+
+            sqlx::query("INSERT INTO custom_gifs (guild_id, gif_type, gif_id, gif_url, gif_name) VALUES (?, ?, ?, ?, ?)")
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .bind(gif_id)
+                .bind(gif_url)
+                .bind(gif_name)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(gif_id)
+        })
+    }
+
+    // Removes the GIF and shifts the IDs above it down by one. Returns false if no GIF had that ID.
+    pub async fn delete_custom_gif(&self, guild_id: u64, gif_type: &str, gif_id: u32) -> Result<bool, sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            let deleted = sqlx::query("DELETE FROM custom_gifs WHERE guild_id = ? AND gif_type = ? AND gif_id = ?")
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .bind(gif_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+            if deleted == 0 {
+                return Ok(false);
+            }
+
+            sqlx::query("UPDATE custom_gifs SET gif_id = gif_id - 1 WHERE guild_id = ? AND gif_type = ? AND gif_id > ?")
+                .bind(guild_id as i64)
+                .bind(gif_type)
+                .bind(gif_id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    pub async fn get_bot_settings(&self) -> Result<BotSettings, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT glados_gif, roulette_click_gif, roulette_fire_gif FROM bot_settings")
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    // Column is one of the `*_gif` columns in bot_settings, never user input
+    pub async fn set_bot_gif(&self, column: &str, url: &str) -> Result<(), sqlx::Error> {
+        let query = format!("UPDATE bot_settings SET {column} = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query(&query)
+                .bind(url)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+}
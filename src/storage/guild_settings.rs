@@ -0,0 +1,515 @@
+use super::{on_pool, Storage};
+use crate::birthday::{BirthdayMention, LeapDayPolicy};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+
+//--------------------------
+// Row structures
+//--------------------------
+#[derive(sqlx::FromRow, Debug)]
+pub struct BirthdayGuild {
+    pub guild_id: u64,
+    pub birthday_channel: Option<u64>,
+    pub birthday_role: Option<u64>,
+    pub birthday_hour: u8,
+    pub birthday_belated_days: u8,
+    pub birthday_last_announced: Option<NaiveDate>,
+    pub birthday_last_reminded: Option<NaiveDate>,
+    pub leap_day_policy: String,
+    pub birthday_mention: String,
+    pub birthday_mention_role: Option<u64>,
+    pub birthday_message: Option<String>,
+    pub birthday_combine: bool,
+    pub timezone: String,
+}
+
+const BIRTHDAY_GUILD_COLUMNS: &str = "guild_id, birthday_channel, birthday_role, birthday_hour, birthday_belated_days, birthday_last_announced, \
+    birthday_last_reminded, leap_day_policy, birthday_mention, birthday_mention_role, birthday_message, birthday_combine, timezone";
+
+impl BirthdayGuild {
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone)
+    }
+
+    pub fn leap_day(&self) -> LeapDayPolicy {
+        LeapDayPolicy::from_db(&self.leap_day_policy)
+    }
+
+    pub fn mention(&self) -> BirthdayMention {
+        BirthdayMention::from_db(&self.birthday_mention)
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct VcResetGuild {
+    pub guild_id: u64,
+    pub timezone: String,
+    pub vctrack_reset_month: Option<String>,
+}
+
+impl VcResetGuild {
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone)
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct QotdGuild {
+    pub guild_id: u64,
+    pub qotd_channel: u64,
+    pub qotd_hour: u8,
+    pub qotd_last_posted: Option<NaiveDate>,
+    pub timezone: String,
+}
+
+impl QotdGuild {
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone)
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct WelcomeSettings {
+    pub channel_id: Option<u64>,
+    pub image_url: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct BoostSettings {
+    pub channel_id: Option<u64>,
+    pub image_url: Option<String>,
+    pub message: Option<String>,
+}
+
+//--------------------------
+// guild_settings / welcome / boost tables
+//--------------------------
+impl Storage {
+    // Create the per-guild settings rows if they don't exist yet
+    pub async fn register_guild(&self, guild_id: u64) -> Result<(), sqlx::Error> {
+        let insert = self.insert_ignore();
+
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            for table in ["guild_settings", "welcome"] {
+                sqlx::query(&format!("{insert} {table} (guild_id) VALUES (?)"))
+                    .bind(guild_id as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    // Table and column names below are always hardcoded by the caller
+    async fn get_guild_id_column(&self, table: &str, column: &str, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        let query = format!("SELECT {column} FROM {table} WHERE guild_id = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query_scalar(&query)
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    async fn set_guild_id_column(&self, table: &str, column: &str, guild_id: u64, value: Option<u64>) -> Result<(), sqlx::Error> {
+        let query = format!("UPDATE {table} SET {column} = ? WHERE guild_id = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query(&query)
+                .bind(value.map(|v| v as i64))
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    async fn set_guild_text_column(&self, table: &str, column: &str, guild_id: u64, value: Option<&str>) -> Result<(), sqlx::Error> {
+        let query = format!("UPDATE {table} SET {column} = ? WHERE guild_id = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query(&query)
+                .bind(value)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    //--------------------------
+    // Birthdays
+    //--------------------------
+    pub async fn get_birthday_channel(&self, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        self.get_guild_id_column("guild_settings", "birthday_channel", guild_id).await
+    }
+
+    pub async fn set_birthday_channel(&self, guild_id: u64, channel_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "birthday_channel", guild_id, channel_id).await
+    }
+
+    pub async fn set_birthday_role(&self, guild_id: u64, role_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "birthday_role", guild_id, role_id).await
+    }
+
+    // Local hour of the day birthdays are announced at
+    pub async fn set_birthday_hour(&self, guild_id: u64, hour: u8) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_hour = ? WHERE guild_id = ?")
+                .bind(hour)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // How many days late a missed birthday can still be announced, 0 disables belated announcements
+    pub async fn set_birthday_belated_days(&self, guild_id: u64, days: u8) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_belated_days = ? WHERE guild_id = ?")
+                .bind(days)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn set_leap_day_policy(&self, guild_id: u64, policy: LeapDayPolicy) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("guild_settings", "leap_day_policy", guild_id, Some(policy.as_db())).await
+    }
+
+    // Who gets pinged by announcements. The role is only used by BirthdayMention::Role.
+    pub async fn set_birthday_mention(&self, guild_id: u64, mention: BirthdayMention, role_id: Option<u64>) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_mention = ?, birthday_mention_role = ? WHERE guild_id = ?")
+                .bind(mention.as_db())
+                .bind(role_id.map(|r| r as i64))
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Announcement template, None goes back to the default message
+    pub async fn set_birthday_message(&self, guild_id: u64, message: Option<&str>) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("guild_settings", "birthday_message", guild_id, message).await
+    }
+
+    // Whether birthdays on the same day are announced together in one message
+    pub async fn set_birthday_combine(&self, guild_id: u64, combine: bool) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_combine = ? WHERE guild_id = ?")
+                .bind(combine)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Local date of the guild's most recent announcements
+    pub async fn set_birthday_last_announced(&self, guild_id: u64, date: NaiveDate) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_last_announced = ? WHERE guild_id = ?")
+                .bind(date)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Local date of the guild's most recent reminder DMs
+    pub async fn set_birthday_last_reminded(&self, guild_id: u64, date: NaiveDate) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_last_reminded = ? WHERE guild_id = ?")
+                .bind(date)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_birthday_guilds(&self) -> Result<Vec<BirthdayGuild>, sqlx::Error> {
+        let query = format!("SELECT {BIRTHDAY_GUILD_COLUMNS} FROM guild_settings");
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Guilds that had a birthday role before grants were tracked and haven't been swept for old role holders yet
+    pub async fn get_birthday_role_sweep_guilds(&self) -> Result<Vec<BirthdayGuild>, sqlx::Error> {
+        let query = format!("SELECT {BIRTHDAY_GUILD_COLUMNS} FROM guild_settings WHERE birthday_role_sweep_pending = TRUE AND birthday_role IS NOT NULL");
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn finish_birthday_role_sweep(&self, guild_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET birthday_role_sweep_pending = FALSE WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_birthday_guild(&self, guild_id: u64) -> Result<BirthdayGuild, sqlx::Error> {
+        let query = format!("SELECT {BIRTHDAY_GUILD_COLUMNS} FROM guild_settings WHERE guild_id = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    //--------------------------
+    // Quote of the day
+    //--------------------------
+    // Channel None turns the quote of the day off. Hour is local to the guild's timezone, None keeps the stored one.
+    pub async fn set_qotd(&self, guild_id: u64, channel_id: Option<u64>, hour: Option<u8>) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET qotd_channel = ?, qotd_hour = COALESCE(?, qotd_hour) WHERE guild_id = ?")
+                .bind(channel_id.map(|c| c as i64))
+                .bind(hour)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_qotd_hour(&self, guild_id: u64) -> Result<u8, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT qotd_hour FROM guild_settings WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    // Local date of the guild's most recent quote of the day
+    pub async fn set_qotd_last_posted(&self, guild_id: u64, date: NaiveDate) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET qotd_last_posted = ? WHERE guild_id = ?")
+                .bind(date)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Guilds with a quote of the day channel set
+    pub async fn get_qotd_guilds(&self) -> Result<Vec<QotdGuild>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT guild_id, qotd_channel, qotd_hour, qotd_last_posted, timezone FROM guild_settings WHERE qotd_channel IS NOT NULL")
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    //--------------------------
+    // Timezone
+    //--------------------------
+    pub async fn get_timezone(&self, guild_id: u64) -> Result<Tz, sqlx::Error> {
+        let timezone: String = on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT timezone FROM guild_settings WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await?
+        });
+
+        Ok(parse_timezone(&timezone))
+    }
+
+    pub async fn set_timezone(&self, guild_id: u64, timezone: Tz) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("guild_settings", "timezone", guild_id, Some(timezone.name())).await
+    }
+
+    //--------------------------
+    // Required roles
+    //--------------------------
+    pub async fn get_quotes_required_role(&self, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        self.get_guild_id_column("guild_settings", "quotes_required_role", guild_id).await
+    }
+
+    pub async fn set_quotes_required_role(&self, guild_id: u64, role_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "quotes_required_role", guild_id, role_id).await
+    }
+
+    pub async fn get_gifs_required_role(&self, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        self.get_guild_id_column("guild_settings", "custom_gifs_required_role", guild_id).await
+    }
+
+    pub async fn set_gifs_required_role(&self, guild_id: u64, role_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "custom_gifs_required_role", guild_id, role_id).await
+    }
+
+    //--------------------------
+    // Channels
+    //--------------------------
+    pub async fn get_vctrack_ignored_channel(&self, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        self.get_guild_id_column("guild_settings", "vctrack_ignored_channel", guild_id).await
+    }
+
+    pub async fn set_vctrack_ignored_channel(&self, guild_id: u64, channel_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "vctrack_ignored_channel", guild_id, channel_id).await
+    }
+
+    pub async fn get_member_leave_channel(&self, guild_id: u64) -> Result<Option<u64>, sqlx::Error> {
+        self.get_guild_id_column("guild_settings", "member_leave_channel_id", guild_id).await
+    }
+
+    pub async fn set_member_leave_channel(&self, guild_id: u64, channel_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("guild_settings", "member_leave_channel_id", guild_id, channel_id).await
+    }
+
+    //--------------------------
+    // VC tracking
+    //--------------------------
+    pub async fn get_vc_reset_guilds(&self) -> Result<Vec<VcResetGuild>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT guild_id, timezone, vctrack_reset_month FROM guild_settings")
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Month is formatted as YYYY-MM in the guild's timezone
+    pub async fn set_vc_reset_month(&self, guild_id: u64, month: &str) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("guild_settings", "vctrack_reset_month", guild_id, Some(month)).await
+    }
+
+    //--------------------------
+    // Roulette
+    //--------------------------
+    // Returns (chamber, count)
+    pub async fn get_roulette(&self, guild_id: u64) -> Result<(u8, u8), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT roulette_chamber, roulette_count FROM guild_settings WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_roulette(&self, guild_id: u64, chamber: u8, count: u8) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE guild_settings SET roulette_chamber = ?, roulette_count = ? WHERE guild_id = ?")
+                .bind(chamber)
+                .bind(count)
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Reset the gun and count the death against the user
+    pub async fn record_roulette_death(&self, guild_id: u64, user_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query("UPDATE guild_settings SET roulette_chamber = 0, roulette_count = 0 WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query("UPDATE users SET roulette_deaths = roulette_deaths + 1 WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    //--------------------------
+    // Welcome
+    //--------------------------
+    pub async fn get_welcome_settings(&self, guild_id: u64) -> Result<WelcomeSettings, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT channel_id, image_url, message FROM welcome WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_welcome_channel(&self, guild_id: u64, channel_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("welcome", "channel_id", guild_id, channel_id).await
+    }
+
+    pub async fn set_welcome_message(&self, guild_id: u64, message: Option<&str>) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("welcome", "message", guild_id, message).await
+    }
+
+    pub async fn set_welcome_image(&self, guild_id: u64, image_url: Option<&str>) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("welcome", "image_url", guild_id, image_url).await
+    }
+
+    //--------------------------
+    // Boost
+    //--------------------------
+    pub async fn get_boost_settings(&self, guild_id: u64) -> Result<BoostSettings, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT channel_id, image_url, message FROM boost WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_boost_channel(&self, guild_id: u64, channel_id: Option<u64>) -> Result<(), sqlx::Error> {
+        self.set_guild_id_column("boost", "channel_id", guild_id, channel_id).await
+    }
+
+    pub async fn set_boost_message(&self, guild_id: u64, message: Option<&str>) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("boost", "message", guild_id, message).await
+    }
+
+    pub async fn set_boost_image(&self, guild_id: u64, image_url: Option<&str>) -> Result<(), sqlx::Error> {
+        self.set_guild_text_column("boost", "image_url", guild_id, image_url).await
+    }
+}
+
+// Falls back to UTC if the stored name is no longer a known timezone
+fn parse_timezone(timezone: &str) -> Tz {
+    timezone.parse().unwrap_or(Tz::UTC)
+}
@@ -0,0 +1,33 @@
+use super::{on_pool, Storage};
+
+//--------------------------
+// scheduled_jobs table
+//--------------------------
+impl Storage {
+    // Unix timestamp of the job's last successful run, None if it has never run
+    pub async fn get_job_last_run(&self, job_name: &str) -> Result<Option<i64>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT last_run FROM scheduled_jobs WHERE job_name = ?")
+                .bind(job_name)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    pub async fn set_job_last_run(&self, job_name: &str, last_run: i64) -> Result<(), sqlx::Error> {
+        let query = match self {
+            Storage::MySql(_) => "INSERT INTO scheduled_jobs (job_name, last_run) VALUES (?, ?) ON DUPLICATE KEY UPDATE last_run = VALUES(last_run)",
+            Storage::Sqlite(_) => "INSERT INTO scheduled_jobs (job_name, last_run) VALUES (?, ?) ON CONFLICT (job_name) DO UPDATE SET last_run = excluded.last_run",
+        };
+
+        on_pool!(self, pool => {
+            sqlx::query(query)
+                .bind(job_name)
+                .bind(last_run)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use super::Storage;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use std::collections::HashMap;
+use tracing::info;
+
+//--------------------------
+// Embedded migrations
+//--------------------------
+// Compiled into the binary, so a deploy always carries the schema it expects
+static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+#[derive(Debug, PartialEq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    // Applied, but the script has been edited since
+    ChecksumMismatch,
+    // Applied, but the script is no longer shipped with the bot
+    Missing,
+    // Failed partway through and needs fixing by hand
+    Dirty,
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+impl Storage {
+    fn migrator(&self) -> &'static Migrator {
+        match self {
+            Storage::MySql(_) => &MYSQL_MIGRATOR,
+            Storage::Sqlite(_) => &SQLITE_MIGRATOR,
+        }
+    }
+
+    // Apply every pending migration. Fails without touching the schema if an applied script was modified.
+    pub async fn run_migrations(&self) -> Result<(), sqlx::Error> {
+        match self {
+            Storage::MySql(pool) => {
+                self.adopt_legacy_mysql_schema().await?;
+                MYSQL_MIGRATOR.run(pool).await?
+            },
+            Storage::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+        }
+
+        Ok(())
+    }
+
+    // Databases set up by running the scripts by hand have the tables but no record of which scripts ran.
+    // Record every script from before the bot applied its own migrations whose changes are already in the schema,
+    // so only the missing ones get run. Does nothing once the migrations table exists.
+    async fn adopt_legacy_mysql_schema(&self) -> Result<(), sqlx::Error> {
+        let Storage::MySql(pool) = self else {
+            return Ok(());
+        };
+
+        let table = |table: &str| format!("EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = '{table}')");
+        let column = |table: &str, column: &str| format!("EXISTS (SELECT 1 FROM information_schema.columns \
+            WHERE table_schema = DATABASE() AND table_name = '{table}' AND column_name = '{column}')");
+
+        // EXISTS comes back as a BIGINT on MySQL, not a boolean
+        let has_migrations_table: i64 = sqlx::query_scalar(&format!("SELECT {}", table("_sqlx_migrations")))
+            .fetch_one(pool)
+            .await?;
+        let has_legacy_schema: i64 = sqlx::query_scalar(&format!("SELECT {}", table("users")))
+            .fetch_one(pool)
+            .await?;
+
+        if has_migrations_table != 0 || has_legacy_schema == 0 {
+            return Ok(());
+        }
+
+        // A script counts as applied if what it changed is in the schema. Scripts that only dropped or modified
+        // columns are judged by the scripts that came after them.
+        let legacy_scripts: Vec<(i64, String)> = vec![
+            (20240824214947, table("users")),
+            (20240830212700, table("quotes")),
+            (20240901024044, column("guild_settings", "quotes_required_role")),
+            (20240914040559, column("users", "vctrack_monthly_time")),
+            (20240919210744, column("users", "display_name")),
+            (20240919213825, column("quotes", "sayer_display_name")),
+            (20241112010849, column("guild_settings", "member_leave_channel_id")),
+            (20241121013025, table("custom_gifs")),
+            (20241121014942, column("guild_settings", "custom_gifs_required_role")),
+            (20241210010223, format!("{} OR {}", column("users", "times_quoted"), column("users", "rps_win"))),
+            (20250207003851, format!("NOT {} AND {}", column("users", "times_quoted"), column("users", "rps_win"))),
+            (20250304151858, column("users", "rps_win")),
+            (20250310235724, column("guild_settings", "roulette_chamber")),
+            (20250311172941, column("users", "roulette_deaths")),
+            (20250402174553, format!("{} OR {}", column("custom_gifs", "filename"), column("custom_gifs", "gif_name"))),
+            (20250402183707, format!("{} OR EXISTS (SELECT 1 FROM information_schema.columns \
+                WHERE table_schema = DATABASE() AND table_name = 'custom_gifs' AND column_name = 'filename' AND character_maximum_length = 200)",
+                column("custom_gifs", "gif_name"))),
+            (20250405035318, column("custom_gifs", "gif_name")),
+            (20250408202707, table("user_settings")),
+            (20250413035337, table("boost")),
+            (20250419040545, column("users", "hug_sent")),
+            (20250419043228, table("bot_settings")),
+            (20250426011038, column("bot_settings", "roulette_click_gif")),
+        ];
+
+        let mut conn = pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+
+        for (version, check) in legacy_scripts {
+            let applied: i64 = sqlx::query_scalar(&format!("SELECT {check}"))
+                .fetch_one(&mut *conn)
+                .await?;
+            let migration = MYSQL_MIGRATOR.iter().find(|m| m.version == version);
+
+            if applied != 0 && let Some(migration) = migration {
+                sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, ?, TRUE, ?, 0)")
+                    .bind(migration.version)
+                    .bind(&*migration.description)
+                    .bind(&*migration.checksum)
+                    .execute(&mut *conn)
+                    .await?;
+
+                info!("[ DATABASE ] Found migration {version} already in the schema, marked it as applied");
+            }
+        }
+
+        Ok(())
+    }
+
+    // Make sure the applied migrations still match the embedded scripts without applying anything
+    pub async fn verify_migrations(&self) -> Result<(), sqlx::Error> {
+        for migration in self.migration_status().await? {
+            let error = match migration.state {
+                MigrationState::ChecksumMismatch => MigrateError::VersionMismatch(migration.version),
+                MigrationState::Missing => MigrateError::VersionMissing(migration.version),
+                MigrationState::Dirty => MigrateError::Dirty(migration.version),
+                _ => continue,
+            };
+
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    // Every known migration ordered by version, including applied ones the binary doesn't know about
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+        let (applied, dirty_version) = match self {
+            Storage::MySql(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.ensure_migrations_table().await?;
+                (conn.list_applied_migrations().await?, conn.dirty_version().await?)
+            },
+            Storage::Sqlite(pool) => {
+                let mut conn = pool.acquire().await?;
+                conn.ensure_migrations_table().await?;
+                (conn.list_applied_migrations().await?, conn.dirty_version().await?)
+            }
+        };
+
+        let mut applied: HashMap<i64, _> = applied
+            .into_iter()
+            .map(|m| (m.version, m.checksum))
+            .collect();
+
+        let mut status: Vec<MigrationStatus> = self.migrator()
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .map(|m| {
+                let state = if dirty_version == Some(m.version) {
+                    MigrationState::Dirty
+                } else {
+                    match applied.remove(&m.version) {
+                        Some(checksum) if checksum == m.checksum => MigrationState::Applied,
+                        Some(_) => MigrationState::ChecksumMismatch,
+                        None => MigrationState::Pending,
+                    }
+                };
+
+                MigrationStatus {
+                    version: m.version,
+                    description: m.description.to_string(),
+                    state,
+                }
+            })
+            .collect();
+
+        // Anything left was applied by a different build of the bot
+        status.extend(applied.into_keys().map(|version| MigrationStatus {
+            version,
+            description: String::new(),
+            state: MigrationState::Missing,
+        }));
+        status.sort_by_key(|m| m.version);
+
+        Ok(status)
+    }
+}
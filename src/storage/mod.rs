@@ -0,0 +1,72 @@
+use sqlx::mysql::MySqlPool;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use std::str::FromStr;
+
+pub mod birthdays;
+pub mod gifs;
+pub mod guild_settings;
+pub mod jobs;
+pub mod migrations;
+pub mod quotes;
+pub mod users;
+
+//--------------------------
+// Storage backend
+//--------------------------
+// Every query the bot runs goes through this type. The backend is picked from the scheme of DATABASE_URL,
+// so `mysql://...` keeps using MySQL while `sqlite://bot.db` runs a small instance without a server.
+//
+// Discord IDs are bound as i64 since SQLite can't encode u64. Snowflakes never use the top bit, so this is lossless.
+#[derive(Debug, Clone)]
+pub enum Storage {
+    MySql(MySqlPool),
+    Sqlite(SqlitePool),
+}
+
+// Run the same body against whichever pool is in use.
+// Only use this when the SQL is valid on both backends, otherwise match on the Storage variant directly.
+macro_rules! on_pool {
+    ($storage:expr, $pool:ident => $body:expr) => {
+        match $storage {
+            $crate::storage::Storage::MySql($pool) => $body,
+            $crate::storage::Storage::Sqlite($pool) => $body,
+        }
+    };
+}
+pub(crate) use on_pool;
+
+impl Storage {
+    pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
+        if database_url.starts_with("sqlite:") {
+            let options = SqliteConnectOptions::from_str(database_url)?
+                .create_if_missing(true);
+
+            Ok(Storage::Sqlite(SqlitePool::connect_with(options).await?))
+        } else {
+            Ok(Storage::MySql(MySqlPool::connect(database_url).await?))
+        }
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Storage::MySql(_) => "MySQL",
+            Storage::Sqlite(_) => "SQLite",
+        }
+    }
+
+    // SQL function used to shuffle rows with ORDER BY
+    fn random_fn(&self) -> &'static str {
+        match self {
+            Storage::MySql(_) => "RAND()",
+            Storage::Sqlite(_) => "RANDOM()",
+        }
+    }
+
+    // Insert statement prefix that skips rows which already exist
+    fn insert_ignore(&self) -> &'static str {
+        match self {
+            Storage::MySql(_) => "INSERT IGNORE INTO",
+            Storage::Sqlite(_) => "INSERT OR IGNORE INTO",
+        }
+    }
+}
@@ -0,0 +1,532 @@
+use super::{on_pool, Storage};
+use crate::quotes::{Quote, QuoteLine, QuoteRevision};
+
+//--------------------------
+// Row structures
+//--------------------------
+// A quote along with how many upvotes it has
+#[derive(sqlx::FromRow)]
+pub struct RatedQuote {
+    #[sqlx(flatten)]
+    pub quote: Quote,
+    pub votes: i64,
+}
+
+// Matches quotes said by a user, either on their own or as one of the speakers in a conversation. Binds the user ID twice.
+const SAID_BY: &str = "(sayer_id = ? OR EXISTS (SELECT 1 FROM quote_lines l WHERE l.guild_id = quotes.guild_id AND l.quote_id = quotes.quote_id AND l.speaker_id = ?))";
+
+// Matches quotes with a tag. Binds the tag once.
+const TAGGED: &str = "EXISTS (SELECT 1 FROM quote_tags t WHERE t.guild_id = quotes.guild_id AND t.quote_id = quotes.quote_id AND t.tag = ?)";
+
+const INSERT_QUOTE: &str = "INSERT INTO quotes (guild_id, adder_id, sayer_id, quote_id, quote, timestamp, adder_display_name, sayer_display_name, message_link, channel_id) \
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+
+//--------------------------
+// quotes table
+//--------------------------
+// Deleted quotes keep their row and ID so they can be restored, every lookup below skips them unless it says otherwise
+impl Storage {
+    // Counts deleted quotes too so IDs are never reused
+    pub async fn get_max_quote_id(&self, guild_id: u64) -> Result<u32, sqlx::Error> {
+        let max_id: Option<u32> = on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT MAX(quote_id) FROM quotes WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await?
+        });
+
+        Ok(max_id.unwrap_or(0))
+    }
+
+    pub async fn insert_quote(&self, quote: &Quote) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query(INSERT_QUOTE)
+                .bind(quote.guild_id as i64)
+                .bind(quote.adder_id as i64)
+                .bind(quote.sayer_id as i64)
+                .bind(quote.quote_id)
+                .bind(&quote.quote)
+                .bind(quote.timestamp)
+                .bind(&quote.adder_display_name)
+                .bind(&quote.sayer_display_name)
+                .bind(&quote.message_link)
+                .bind(quote.channel_id.map(|c| c as i64))
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_quote(&self, guild_id: u64, quote_id: u32) -> Result<Option<Quote>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM quotes WHERE guild_id = ? AND quote_id = ? AND deleted_at IS NULL")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    // Quote saved from a specific message, used to stop the same message being quoted twice. Includes deleted quotes.
+    pub async fn find_quote_by_link(&self, guild_id: u64, message_link: &str) -> Result<Option<Quote>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM quotes WHERE guild_id = ? AND message_link = ?")
+                .bind(guild_id as i64)
+                .bind(message_link)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    // Pick a random quote from the guild, optionally only ones said by a specific user and/or with a tag
+    pub async fn get_random_quote(&self, guild_id: u64, sayer_id: Option<u64>, tag: Option<&str>) -> Result<Option<Quote>, sqlx::Error> {
+        let sayer_filter = if sayer_id.is_some() { format!(" AND {SAID_BY}") } else { String::new() };
+        let tag_filter = if tag.is_some() { format!(" AND {TAGGED}") } else { String::new() };
+        let query = format!("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NULL{sayer_filter}{tag_filter} ORDER BY {} LIMIT 1", self.random_fn());
+
+        on_pool!(self, pool => {
+            let mut query = sqlx::query_as(&query).bind(guild_id as i64);
+
+            if let Some(sayer_id) = sayer_id {
+                query = query.bind(sayer_id as i64).bind(sayer_id as i64);
+            }
+            if let Some(tag) = tag {
+                query = query.bind(tag);
+            }
+
+            query.fetch_optional(pool).await
+        })
+    }
+
+    // Every quote containing all of the search terms, optionally only ones said by a specific user
+    pub async fn search_quotes(&self, guild_id: u64, terms: &[String], sayer_id: Option<u64>) -> Result<Vec<Quote>, sqlx::Error> {
+        // '!' is used as the escape character since MySQL and SQLite disagree on backslashes
+        let term_filter = " AND quote LIKE ? ESCAPE '!'".repeat(terms.len());
+        let sayer_filter = if sayer_id.is_some() { format!(" AND {SAID_BY}") } else { String::new() };
+        let query = format!("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NULL{term_filter}{sayer_filter} ORDER BY quote_id");
+
+        on_pool!(self, pool => {
+            let mut query = sqlx::query_as(&query).bind(guild_id as i64);
+
+            for term in terms {
+                let escaped = term.replace('!', "!!").replace('%', "!%").replace('_', "!_");
+                query = query.bind(format!("%{escaped}%"));
+            }
+
+            if let Some(sayer_id) = sayer_id {
+                query = query.bind(sayer_id as i64).bind(sayer_id as i64);
+            }
+
+            query.fetch_all(pool).await
+        })
+    }
+
+    // Mark a quote as deleted. Returns false if there was no quote to delete.
+    pub async fn delete_quote(&self, guild_id: u64, quote_id: u32, deleted_by: u64, deleted_at: i64) -> Result<bool, sqlx::Error> {
+        let deleted = on_pool!(self, pool => {
+            sqlx::query("UPDATE quotes SET deleted_at = ?, deleted_by = ? WHERE guild_id = ? AND quote_id = ? AND deleted_at IS NULL")
+                .bind(deleted_at)
+                .bind(deleted_by as i64)
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(deleted > 0)
+    }
+
+    // Bring back a deleted quote under its old ID. Returns false if there was no deleted quote with that ID.
+    pub async fn restore_quote(&self, guild_id: u64, quote_id: u32) -> Result<bool, sqlx::Error> {
+        let restored = on_pool!(self, pool => {
+            sqlx::query("UPDATE quotes SET deleted_at = NULL, deleted_by = NULL WHERE guild_id = ? AND quote_id = ? AND deleted_at IS NOT NULL")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(restored > 0)
+    }
+
+    // Deleted quotes, most recently deleted first
+    pub async fn list_deleted_quotes(&self, guild_id: u64) -> Result<Vec<Quote>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NOT NULL ORDER BY deleted_at DESC, quote_id DESC")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Every quote in the guild ordered by ID, optionally only ones said by a specific user and/or with a tag
+    pub async fn list_quotes(&self, guild_id: u64, sayer_id: Option<u64>, tag: Option<&str>) -> Result<Vec<Quote>, sqlx::Error> {
+        let sayer_filter = if sayer_id.is_some() { format!(" AND {SAID_BY}") } else { String::new() };
+        let tag_filter = if tag.is_some() { format!(" AND {TAGGED}") } else { String::new() };
+        let query = format!("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NULL{sayer_filter}{tag_filter} ORDER BY quote_id");
+
+        on_pool!(self, pool => {
+            let mut query = sqlx::query_as(&query).bind(guild_id as i64);
+
+            if let Some(sayer_id) = sayer_id {
+                query = query.bind(sayer_id as i64).bind(sayer_id as i64);
+            }
+            if let Some(tag) = tag {
+                query = query.bind(tag);
+            }
+
+            query.fetch_all(pool).await
+        })
+    }
+
+    // Pick a random quote that hasn't been featured yet, without marking it. Once every quote has had its turn
+    // any quote can be picked again except the last featured one, so the same quote can't show up twice in a row.
+    pub async fn pick_quote_of_the_day(&self, guild_id: u64) -> Result<Option<Quote>, sqlx::Error> {
+        let unfeatured_query = format!("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NULL AND featured_at IS NULL ORDER BY {} LIMIT 1", self.random_fn());
+        let repeat_query = format!("SELECT * FROM quotes WHERE guild_id = ? AND deleted_at IS NULL AND quote_id != ? ORDER BY {} LIMIT 1", self.random_fn());
+
+        on_pool!(self, pool => {
+            let quote: Option<Quote> = sqlx::query_as(&unfeatured_query)
+                .bind(guild_id as i64)
+                .fetch_optional(pool)
+                .await?;
+
+            if quote.is_some() {
+                return Ok(quote);
+            }
+
+            let last_featured: Option<u32> = sqlx::query_scalar("SELECT quote_id FROM quotes WHERE guild_id = ? AND featured_at IS NOT NULL ORDER BY featured_at DESC LIMIT 1")
+                .bind(guild_id as i64)
+                .fetch_optional(pool)
+                .await?;
+
+            let quote: Option<Quote> = sqlx::query_as(&repeat_query)
+                .bind(guild_id as i64)
+                .bind(last_featured.unwrap_or(0))
+                .fetch_optional(pool)
+                .await?;
+
+            // Only one quote in the guild, so it has to repeat
+            if quote.is_none() {
+                return sqlx::query_as(&repeat_query)
+                    .bind(guild_id as i64)
+                    .bind(0)
+                    .fetch_optional(pool)
+                    .await;
+            }
+
+            Ok(quote)
+        })
+    }
+
+    // Mark a quote picked by pick_quote_of_the_day as featured once it has been posted.
+    // Featuring a quote that was already featured means every quote has had its turn, so a new rotation starts.
+    pub async fn set_quote_featured(&self, guild_id: u64, quote_id: u32, featured_at: i64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            let already_featured: Option<i64> = sqlx::query_scalar("SELECT featured_at FROM quotes WHERE guild_id = ? AND quote_id = ?")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .flatten();
+
+            if already_featured.is_some() {
+                sqlx::query("UPDATE quotes SET featured_at = NULL WHERE guild_id = ?")
+                    .bind(guild_id as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            sqlx::query("UPDATE quotes SET featured_at = ? WHERE guild_id = ? AND quote_id = ?")
+                .bind(featured_at)
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    // Returns (quotes added, times quoted) for a user. Being one of the speakers in a conversation counts as being quoted.
+    pub async fn get_quote_counts(&self, guild_id: u64, user_id: u64) -> Result<(i64, i64), sqlx::Error> {
+        let query = format!("SELECT
+                COUNT(CASE WHEN adder_id = ? THEN 1 END),
+                COUNT(CASE WHEN {SAID_BY} THEN 1 END)
+            FROM quotes WHERE guild_id = ? AND deleted_at IS NULL");
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .bind(user_id as i64)
+                .bind(user_id as i64)
+                .bind(user_id as i64)
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn count_quotes(&self, guild_id: u64) -> Result<i64, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT COUNT(quote_id) FROM quotes WHERE guild_id = ? AND deleted_at IS NULL")
+                .bind(guild_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+}
+
+//--------------------------
+// quote_lines table
+//--------------------------
+impl Storage {
+    // Add a conversation quote along with its lines, in order
+    pub async fn insert_conversation_quote(&self, quote: &Quote, lines: &[QuoteLine]) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(INSERT_QUOTE)
+                .bind(quote.guild_id as i64)
+                .bind(quote.adder_id as i64)
+                .bind(quote.sayer_id as i64)
+                .bind(quote.quote_id)
+                .bind(&quote.quote)
+                .bind(quote.timestamp)
+                .bind(&quote.adder_display_name)
+                .bind(&quote.sayer_display_name)
+                .bind(&quote.message_link)
+                .bind(quote.channel_id.map(|c| c as i64))
+                .execute(&mut *tx)
+                .await?;
+
+            for (i, line) in lines.iter().enumerate() {
+                sqlx::query("INSERT INTO quote_lines (guild_id, quote_id, line_number, speaker_id, speaker_display_name, line) VALUES (?, ?, ?, ?, ?, ?)")
+                    .bind(quote.guild_id as i64)
+                    .bind(quote.quote_id)
+                    .bind(i as u8 + 1)
+                    .bind(line.speaker_id as i64)
+                    .bind(&line.speaker_display_name)
+                    .bind(&line.line)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    // Lines of a conversation quote in order. Empty for quotes with a single sayer.
+    pub async fn get_quote_lines(&self, guild_id: u64, quote_id: u32) -> Result<Vec<QuoteLine>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT speaker_id, speaker_display_name, line FROM quote_lines WHERE guild_id = ? AND quote_id = ? ORDER BY line_number")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
+
+//--------------------------
+// quote_votes table
+//--------------------------
+impl Storage {
+    // Add the member's upvote, or take it back if they already voted. Returns true if the quote is now upvoted by them.
+    pub async fn toggle_quote_vote(&self, guild_id: u64, quote_id: u32, user_id: u64) -> Result<bool, sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            let removed = sqlx::query("DELETE FROM quote_votes WHERE guild_id = ? AND quote_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .bind(user_id as i64)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+            if removed == 0 {
+                sqlx::query("INSERT INTO quote_votes (guild_id, quote_id, user_id) VALUES (?, ?, ?)")
+                    .bind(guild_id as i64)
+                    .bind(quote_id)
+                    .bind(user_id as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(removed == 0)
+        })
+    }
+
+    pub async fn count_quote_votes(&self, guild_id: u64, quote_id: u32) -> Result<i64, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT COUNT(user_id) FROM quote_votes WHERE guild_id = ? AND quote_id = ?")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    // Quotes with at least one upvote, highest rated first
+    pub async fn get_top_quotes(&self, guild_id: u64) -> Result<Vec<RatedQuote>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT q.*, COUNT(v.user_id) AS votes FROM quotes q \
+                JOIN quote_votes v ON v.guild_id = q.guild_id AND v.quote_id = q.quote_id \
+                WHERE q.guild_id = ? AND q.deleted_at IS NULL \
+                GROUP BY q.guild_id, q.quote_id ORDER BY votes DESC, q.quote_id")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // (member ID, times quoted) for everyone who has been quoted, most quoted first.
+    // Every speaker in a conversation counts, but only once per quote.
+    pub async fn get_most_quoted(&self, guild_id: u64) -> Result<Vec<(u64, i64)>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT s.member_id, COUNT(*) AS times_quoted FROM ( \
+                    SELECT guild_id, quote_id, sayer_id AS member_id FROM quotes WHERE guild_id = ? AND deleted_at IS NULL \
+                    UNION \
+                    SELECT l.guild_id, l.quote_id, l.speaker_id FROM quote_lines l \
+                    JOIN quotes q ON q.guild_id = l.guild_id AND q.quote_id = l.quote_id \
+                    WHERE l.guild_id = ? AND q.deleted_at IS NULL \
+                ) s GROUP BY s.member_id ORDER BY times_quoted DESC, s.member_id")
+                .bind(guild_id as i64)
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
+
+//--------------------------
+// quote_tags table
+//--------------------------
+impl Storage {
+    // Tags the quote already has are skipped
+    pub async fn add_quote_tags(&self, guild_id: u64, quote_id: u32, tags: &[String]) -> Result<(), sqlx::Error> {
+        let query = format!("{} quote_tags (guild_id, quote_id, tag) VALUES (?, ?, ?)", self.insert_ignore());
+
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            for tag in tags {
+                sqlx::query(&query)
+                    .bind(guild_id as i64)
+                    .bind(quote_id)
+                    .bind(tag)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    // Returns false if the quote didn't have the tag
+    pub async fn remove_quote_tag(&self, guild_id: u64, quote_id: u32, tag: &str) -> Result<bool, sqlx::Error> {
+        let removed = on_pool!(self, pool => {
+            sqlx::query("DELETE FROM quote_tags WHERE guild_id = ? AND quote_id = ? AND tag = ?")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .bind(tag)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(removed > 0)
+    }
+
+    pub async fn get_quote_tags(&self, guild_id: u64, quote_id: u32) -> Result<Vec<String>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT tag FROM quote_tags WHERE guild_id = ? AND quote_id = ? ORDER BY tag")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // (quote ID, tag) for every tagged quote in the guild, so lists can show tags without a query per quote
+    pub async fn get_guild_quote_tags(&self, guild_id: u64) -> Result<Vec<(u32, String)>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT quote_id, tag FROM quote_tags WHERE guild_id = ? ORDER BY quote_id, tag")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Tags in use on the guild's quotes containing the given text, for autocomplete
+    pub async fn search_quote_tags(&self, guild_id: u64, partial: &str) -> Result<Vec<String>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT DISTINCT t.tag FROM quote_tags t \
+                JOIN quotes q ON q.guild_id = t.guild_id AND q.quote_id = t.quote_id \
+                WHERE t.guild_id = ? AND q.deleted_at IS NULL AND t.tag LIKE ? ESCAPE '!' ORDER BY t.tag LIMIT 25")
+                .bind(guild_id as i64)
+                .bind(format!("%{}%", partial.replace('!', "!!").replace('%', "!%").replace('_', "!_")))
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
+
+//--------------------------
+// quote_revisions table
+//--------------------------
+impl Storage {
+    // Save the quote's current text, sayer and date as a revision, then apply the edit
+    pub async fn edit_quote(&self, edited: &Quote, editor_id: u64, edited_at: i64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query("INSERT INTO quote_revisions (guild_id, quote_id, editor_id, edited_at, old_quote, old_sayer_id, old_sayer_display_name, old_timestamp) \
+                SELECT guild_id, quote_id, ?, ?, quote, sayer_id, sayer_display_name, timestamp FROM quotes WHERE guild_id = ? AND quote_id = ?")
+                .bind(editor_id as i64)
+                .bind(edited_at)
+                .bind(edited.guild_id as i64)
+                .bind(edited.quote_id)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query("UPDATE quotes SET quote = ?, sayer_id = ?, sayer_display_name = ?, timestamp = ? WHERE guild_id = ? AND quote_id = ?")
+                .bind(&edited.quote)
+                .bind(edited.sayer_id as i64)
+                .bind(&edited.sayer_display_name)
+                .bind(edited.timestamp)
+                .bind(edited.guild_id as i64)
+                .bind(edited.quote_id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    // Every earlier version of a quote, oldest first
+    pub async fn get_quote_revisions(&self, guild_id: u64, quote_id: u32) -> Result<Vec<QuoteRevision>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT editor_id, edited_at, old_quote, old_sayer_display_name, old_timestamp FROM quote_revisions \
+                WHERE guild_id = ? AND quote_id = ? ORDER BY revision_id")
+                .bind(guild_id as i64)
+                .bind(quote_id)
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
@@ -0,0 +1,265 @@
+use super::{on_pool, Storage};
+use crate::data::User;
+
+//--------------------------
+// users / user_settings tables
+//--------------------------
+impl Storage {
+    pub async fn user_exists(&self, guild_id: u64, user_id: u64) -> Result<bool, sqlx::Error> {
+        let count: i64 = on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT COUNT(user_id) FROM users WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_one(pool)
+                .await?
+        });
+
+        Ok(count != 0)
+    }
+
+    pub async fn insert_user(&self, guild_id: u64, user_id: u64, display_name: &str) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("INSERT INTO users (guild_id, user_id, display_name) VALUES (?, ?, ?)")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(display_name)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn insert_user_settings(&self, guild_id: u64, user_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("INSERT INTO user_settings (guild_id, user_id) VALUES (?, ?)")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Returns true if the stored name was changed
+    pub async fn update_display_name(&self, guild_id: u64, user_id: u64, display_name: &str) -> Result<bool, sqlx::Error> {
+        let result = on_pool!(self, pool => {
+            sqlx::query("UPDATE users SET display_name = ? WHERE guild_id = ? AND user_id = ? AND display_name != ?")
+                .bind(display_name)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(display_name)
+                .execute(pool)
+                .await?
+                .rows_affected()
+        });
+
+        Ok(result == 1)
+    }
+
+    pub async fn get_display_name(&self, guild_id: u64, user_id: u64) -> Result<Option<String>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT display_name FROM users WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    // Remove everything tied to a member who left the guild
+    pub async fn delete_member_data(&self, guild_id: u64, user_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+
+            for table in [
+                "birthday",
+                "users",
+                "user_settings",
+                "birthday_reminders",
+                "birthday_role_grants",
+                "birthday_announcements",
+                "quote_votes",
+            ] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE guild_id = ? AND user_id = ?"))
+                    .bind(guild_id as i64)
+                    .bind(user_id as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            // Nobody needs reminding about their birthday anymore
+            sqlx::query("DELETE FROM birthday_reminders WHERE guild_id = ? AND target_user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_user(&self, guild_id: u64, user_id: u64) -> Result<User, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM users WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn get_guild_users(&self, guild_id: u64) -> Result<Vec<User>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_as("SELECT * FROM users WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    // Add one to a stat counter, e.g. "cookie_sent" or "bomb_defused"
+    // The column name is never user input, so it is safe to format into the query.
+    pub async fn increment_user_stat(&self, guild_id: u64, user_id: u64, stat: &str) -> Result<(), sqlx::Error> {
+        let query = format!("UPDATE users SET {stat} = {stat} + 1 WHERE guild_id = ? AND user_id = ?");
+
+        on_pool!(self, pool => {
+            sqlx::query(&query)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_command_ping(&self, guild_id: u64, user_id: u64) -> Result<bool, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT command_ping FROM user_settings WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_command_ping(&self, guild_id: u64, user_id: u64, command_ping: bool) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE user_settings SET command_ping = ? WHERE guild_id = ? AND user_id = ?")
+                .bind(command_ping)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // How many days before a birthday the user wants a reminder DM, None if they haven't opted in
+    pub async fn get_birthday_reminder_days(&self, guild_id: u64, user_id: u64) -> Result<Option<u8>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT birthday_reminder_days FROM user_settings WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_birthday_reminder_days(&self, guild_id: u64, user_id: u64, days: Option<u8>) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE user_settings SET birthday_reminder_days = ? WHERE guild_id = ? AND user_id = ?")
+                .bind(days)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    //--------------------------
+    // VC tracking
+    //--------------------------
+    // Users that the database thinks are still sitting in a VC
+    pub async fn get_vc_joined_users(&self, guild_id: u64) -> Result<Vec<u64>, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT user_id FROM users WHERE guild_id = ? AND vctrack_join_time != 0")
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn get_vc_join_time(&self, guild_id: u64, user_id: u64) -> Result<u32, sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query_scalar("SELECT vctrack_join_time FROM users WHERE guild_id = ? AND user_id = ?")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    pub async fn set_vc_join_time(&self, guild_id: u64, user_id: u64, join_time: i64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE users SET vctrack_join_time = ? WHERE guild_id = ? AND user_id = ?")
+                .bind(join_time)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    // Add the time since the user joined to their totals, then set a new join time (0 if they left)
+    pub async fn add_vc_time(&self, guild_id: u64, user_id: u64, now: i64, new_join_time: i64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE users SET
+                    vctrack_total_time = vctrack_total_time + (? - vctrack_join_time),
+                    vctrack_monthly_time = vctrack_monthly_time + (? - vctrack_join_time),
+                    vctrack_join_time = ?
+                WHERE guild_id = ? AND user_id = ?")
+                .bind(now)
+                .bind(now)
+                .bind(new_join_time)
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+
+    pub async fn get_vc_leaderboard(&self, guild_id: u64, monthly: bool) -> Result<Vec<(String, u32)>, sqlx::Error> {
+        let column = if monthly { "vctrack_monthly_time" } else { "vctrack_total_time" };
+        let query = format!("SELECT display_name, {column} FROM users WHERE guild_id = ? ORDER BY {column} DESC LIMIT 10");
+
+        on_pool!(self, pool => {
+            sqlx::query_as(&query)
+                .bind(guild_id as i64)
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    pub async fn reset_monthly_vc_time(&self, guild_id: u64) -> Result<(), sqlx::Error> {
+        on_pool!(self, pool => {
+            sqlx::query("UPDATE users SET vctrack_monthly_time = 0 WHERE guild_id = ?")
+                .bind(guild_id as i64)
+                .execute(pool)
+                .await?;
+        });
+
+        Ok(())
+    }
+}